@@ -0,0 +1,37 @@
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use njord::matcher::config::MatcherConfig;
+use njord::matcher::decisions::Decisions;
+use njord::matcher::exchange::ExchangeRate;
+use njord::matcher::match_transactions;
+use rust_decimal::Decimal;
+
+#[path = "../tests/common/mod.rs"]
+mod common;
+use common::synthetic;
+
+fn matching(c: &mut Criterion) {
+	// With tolerances and rates the index has wider amount ranges to look through, and transfers with a fee or in another currency are matched too.
+	let tolerant = MatcherConfig {
+		fee_tolerance_amount: Decimal::new(200, 2),
+		fee_tolerance_percentage: 0.5,
+		exchange_rates: vec![ExchangeRate { from: "EUR".into(), to: "SEK".into(), rate: Decimal::new(115, 1) }],
+		..MatcherConfig::default()
+	};
+	let configs = [("exact", MatcherConfig::default()), ("tolerant", tolerant)];
+	let mut group = c.benchmark_group("match_transactions");
+	group.sample_size(10);
+
+	for count in [1_000, 10_000, 100_000] {
+		let raw_transactions = synthetic::transactions(count, 1);
+		for (name, config) in &configs {
+			group.bench_with_input(BenchmarkId::new(*name, count), &raw_transactions, |b, raw_transactions| {
+				b.iter(|| match_transactions(raw_transactions, vec![], config, &mut Decisions::default(), false).unwrap());
+			});
+		}
+	}
+
+	group.finish();
+}
+
+criterion_group!(benches, matching);
+criterion_main!(benches);
@@ -0,0 +1,368 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use chrono::{Duration, Utc};
+use clap::Parser;
+use color_eyre::eyre;
+use color_eyre::eyre::{eyre, WrapErr};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+static API_PREFIX: &str = "/api/v2/";
+static LINK_PREFIX: &str = "/link/";
+static SANDBOX_INSTITUTION_ID: &str = "SANDBOXFINANCE_SFIN0000";
+
+/// Offline stand-in for the subset of the Nordigen v2 API used by njord
+#[derive(Debug, Parser)]
+#[command(version, about)]
+struct Cli {
+	/// Port to listen on, 0 picks a free port
+	#[arg(long, default_value_t = 8080)]
+	port: u16,
+
+	/// Directory with institutions.json and accounts/<id>.json fixtures
+	#[arg(long)]
+	fixtures: PathBuf,
+
+	/// Successful calls allowed per account and endpoint before answering 429
+	#[arg(long, default_value_t = 4)]
+	daily_limit: u64,
+
+	/// Link requisitions as soon as they are created instead of when their link is visited
+	#[arg(long)]
+	auto_link: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct AccountFixture {
+	institution_id: String,
+	details: Value,
+	transactions: Value,
+	balances: Value,
+}
+
+struct Requisition {
+	institution_id: String,
+	status: &'static str,
+	created: String,
+	accounts: Vec<String>,
+	redirect: String,
+}
+
+struct MockNordigen {
+	cli: Cli,
+	base_url: String,
+	institutions: Vec<Value>,
+	accounts: HashMap<String, AccountFixture>,
+	requisitions: HashMap<String, Requisition>,
+	access_tokens: Vec<String>,
+	refresh_tokens: Vec<String>,
+	successful_calls: HashMap<String, u64>,
+	next_id: u64,
+}
+
+struct MockResponse {
+	status: u16,
+	body: String,
+	content_type: &'static str,
+	headers: Vec<(String, String)>,
+}
+
+impl MockResponse {
+	fn json(status: u16, body: Value) -> MockResponse {
+		MockResponse { status, body: body.to_string(), content_type: "application/json", headers: vec![] }
+	}
+
+	fn error(status: u16, summary: &str, detail: &str) -> MockResponse {
+		MockResponse::json(status, json!({ "summary": summary, "detail": detail, "status_code": status }))
+	}
+
+	fn not_found() -> MockResponse {
+		MockResponse::error(404, "Not found.", "Not found.")
+	}
+}
+
+fn main() -> eyre::Result<()> {
+	color_eyre::install()?;
+	let cli = Cli::parse();
+
+	let server = Server::http(("127.0.0.1", cli.port))
+		.map_err(|err| eyre!("unable to start server: {err}"))?;
+	let address = server.server_addr().to_ip()
+		.ok_or_else(|| eyre!("server is not listening on an IP address"))?;
+
+	let mut mock = MockNordigen::load(cli, format!("http://{address}"))?;
+
+	println!("Listening on {}/api/v2", mock.base_url);
+	std::io::stdout().flush()?;
+
+	// A client hanging up before its response is written should not take the server down for everyone else.
+	for request in server.incoming_requests() {
+		if let Err(err) = mock.handle(request) {
+			eprintln!("unable to handle request: {err:#}");
+		}
+	}
+
+	Ok(())
+}
+
+impl MockNordigen {
+	fn load(cli: Cli, base_url: String) -> eyre::Result<MockNordigen> {
+		let institutions = read_json(&cli.fixtures.join("institutions.json"))?;
+
+		let mut accounts = HashMap::new();
+		let accounts_dir = cli.fixtures.join("accounts");
+		for entry in fs::read_dir(&accounts_dir).wrap_err_with(|| format!("unable to read {}", accounts_dir.display()))? {
+			let path = entry?.path();
+			let Some(id) = path.file_stem().and_then(|stem| stem.to_str()) else { continue };
+			accounts.insert(id.to_string(), read_json(&path)?);
+		}
+
+		Ok(MockNordigen {
+			cli,
+			base_url,
+			institutions,
+			accounts,
+			requisitions: HashMap::new(),
+			access_tokens: vec![],
+			refresh_tokens: vec![],
+			successful_calls: HashMap::new(),
+			next_id: 1,
+		})
+	}
+
+	fn handle(&mut self, mut request: Request) -> eyre::Result<()> {
+		let method = request.method().clone();
+		let url = request.url().to_string();
+		let path = url.split('?').next().unwrap_or_default().to_string();
+
+		let mut body = String::new();
+		request.as_reader().read_to_string(&mut body)?;
+
+		let authorization = request.headers().iter()
+			.find(|header| header.field.equiv("Authorization"))
+			.map(|header| header.value.to_string());
+
+		let response = if let Some(requisition_id) = path.strip_prefix(LINK_PREFIX) {
+			self.visit_link(requisition_id.trim_end_matches('/'))
+		} else if let Some(endpoint) = path.strip_prefix(API_PREFIX) {
+			let segments = endpoint.split('/').filter(|segment| !segment.is_empty()).collect::<Vec<_>>();
+			self.route(&method, &segments, authorization.as_deref(), &body)
+		} else {
+			MockResponse::not_found()
+		};
+
+		eprintln!("{method} {url} -> {}", response.status);
+
+		let mut res = Response::from_string(response.body)
+			.with_status_code(response.status)
+			.with_header(header("Content-Type", response.content_type));
+		for (name, value) in &response.headers {
+			res = res.with_header(header(name, value));
+		}
+
+		request.respond(res)?;
+
+		Ok(())
+	}
+
+	fn route(&mut self, method: &Method, segments: &[&str], authorization: Option<&str>, body: &str) -> MockResponse {
+		match (method, segments) {
+			(Method::Post, ["token", "new"]) => return self.new_token(body),
+			(Method::Post, ["token", "refresh"]) => return self.refresh_token(body),
+			_ => {},
+		}
+
+		let is_authorized = authorization
+			.and_then(|authorization| authorization.strip_prefix("Bearer "))
+			.is_some_and(|token| self.access_tokens.iter().any(|access| access == token));
+		if !is_authorized {
+			return MockResponse::error(401, "Invalid token", "Token is invalid or expired");
+		}
+
+		match (method, segments) {
+			(Method::Get, ["institutions"]) => MockResponse::json(200, Value::Array(self.institutions.clone())),
+			(Method::Post, ["requisitions"]) => self.new_requisition(body),
+			(Method::Get, ["requisitions", id]) => self.get_requisition(id),
+			(Method::Get, ["accounts", id, "details"]) => self.account_resource(id, "details", |account| &account.details),
+			(Method::Get, ["accounts", id, "transactions"]) => self.account_resource(id, "transactions", |account| &account.transactions),
+			(Method::Get, ["accounts", id, "balances"]) => self.account_resource(id, "balances", |account| &account.balances),
+			_ => MockResponse::not_found(),
+		}
+	}
+
+	fn new_token(&mut self, body: &str) -> MockResponse {
+		let Ok(body) = serde_json::from_str::<Value>(body) else {
+			return MockResponse::error(400, "Invalid request body", "Body is not valid JSON");
+		};
+
+		for field in ["secret_id", "secret_key"] {
+			if body[field].as_str().unwrap_or_default().is_empty() {
+				return MockResponse::json(400, json!({
+					field: { "summary": "This field may not be blank.", "detail": "This field may not be blank." },
+					"status_code": 400,
+				}));
+			}
+		}
+
+		let access = self.generate_id("access");
+		let refresh = self.generate_id("refresh");
+		self.access_tokens.push(access.clone());
+		self.refresh_tokens.push(refresh.clone());
+
+		MockResponse::json(200, json!({
+			"access": access,
+			"access_expires": 86400,
+			"refresh": refresh,
+			"refresh_expires": 2592000,
+		}))
+	}
+
+	fn refresh_token(&mut self, body: &str) -> MockResponse {
+		let refresh = serde_json::from_str::<Value>(body).ok()
+			.and_then(|body| body["refresh"].as_str().map(str::to_string))
+			.unwrap_or_default();
+
+		if !self.refresh_tokens.contains(&refresh) {
+			return MockResponse::error(401, "Invalid token", "Token is invalid or expired");
+		}
+
+		let access = self.generate_id("access");
+		self.access_tokens.push(access.clone());
+
+		MockResponse::json(200, json!({ "access": access, "access_expires": 86400 }))
+	}
+
+	fn new_requisition(&mut self, body: &str) -> MockResponse {
+		let body = serde_json::from_str::<Value>(body).unwrap_or_default();
+		let institution_id = body["institution_id"].as_str().unwrap_or_default().to_string();
+
+		let is_known_institution = self.institutions.iter().any(|institution| institution["id"] == institution_id.as_str());
+		if !is_known_institution {
+			let detail = format!("Get Institution IDs from /institutions/?country={{$COUNTRY_CODE}}, {institution_id} is not a valid institution id");
+			return MockResponse::json(400, json!({
+				"institution_id": { "summary": "Unknown Institution ID", "detail": detail },
+				"status_code": 400,
+			}));
+		}
+
+		let id = self.generate_id("requisition");
+		self.requisitions.insert(id.clone(), Requisition {
+			institution_id,
+			status: "CR",
+			created: Utc::now().to_rfc3339(),
+			accounts: vec![],
+			redirect: body["redirect"].as_str().unwrap_or_default().to_string(),
+		});
+
+		if self.cli.auto_link {
+			self.link(&id);
+		}
+
+		MockResponse::json(201, self.requisition_json(&id))
+	}
+
+	fn get_requisition(&self, id: &str) -> MockResponse {
+		if !self.requisitions.contains_key(id) {
+			return MockResponse::not_found();
+		}
+
+		MockResponse::json(200, self.requisition_json(id))
+	}
+
+	// Mirrors the sandbox institution, where following the link grants access to its accounts right away.
+	fn visit_link(&mut self, requisition_id: &str) -> MockResponse {
+		if !self.requisitions.contains_key(requisition_id) {
+			return MockResponse::not_found();
+		}
+
+		self.link(requisition_id);
+
+		let requisition = &self.requisitions[requisition_id];
+		let institution = if requisition.institution_id == SANDBOX_INSTITUTION_ID { "the sandbox institution" } else { &requisition.institution_id };
+
+		MockResponse {
+			status: 200,
+			body: format!("<html><body><p>Access to {institution} granted, return to njord.</p><p>{}</p></body></html>", requisition.redirect),
+			content_type: "text/html; charset=utf-8",
+			headers: vec![],
+		}
+	}
+
+	fn link(&mut self, requisition_id: &str) {
+		let Some(requisition) = self.requisitions.get_mut(requisition_id) else { return };
+
+		let mut accounts = self.accounts.iter()
+			.filter(|(_, account)| account.institution_id == requisition.institution_id)
+			.map(|(id, _)| id.clone())
+			.collect::<Vec<_>>();
+		accounts.sort();
+
+		requisition.status = "LN";
+		requisition.accounts = accounts;
+	}
+
+	fn requisition_json(&self, id: &str) -> Value {
+		let requisition = &self.requisitions[id];
+
+		json!({
+			"id": id,
+			"created": requisition.created,
+			"redirect": requisition.redirect,
+			"status": requisition.status,
+			"institution_id": requisition.institution_id,
+			"agreement": null,
+			"reference": id,
+			"accounts": requisition.accounts,
+			"link": format!("{}{LINK_PREFIX}{id}", self.base_url),
+		})
+	}
+
+	fn account_resource(&mut self, id: &str, resource: &str, select: fn(&AccountFixture) -> &Value) -> MockResponse {
+		let is_linked = self.requisitions.values()
+			.any(|requisition| requisition.status == "LN" && requisition.accounts.iter().any(|account| account == id));
+		let Some(account) = self.accounts.get(id).filter(|_| is_linked) else {
+			return MockResponse::error(404, "Account ID not found", &format!("Account ID {id} not found"));
+		};
+		let body = select(account).clone();
+
+		let calls = self.successful_calls.entry(format!("{id}/{resource}")).or_default();
+		let seconds_til_reset = (Utc::now().date_naive().succ_opt().unwrap_or_default().and_hms_opt(0, 0, 0).unwrap_or_default() - Utc::now().naive_utc())
+			.max(Duration::zero())
+			.num_seconds();
+
+		let mut response = if *calls < self.cli.daily_limit {
+			*calls += 1;
+			MockResponse::json(200, body)
+		} else {
+			let detail = format!("Please try again in {seconds_til_reset} seconds");
+			MockResponse::error(429, "Rate limit exceeded", &detail)
+		};
+
+		let remaining = self.cli.daily_limit - *calls;
+		response.headers = vec![
+			("HTTP_X_RATELIMIT_ACCOUNT_SUCCESS_LIMIT".into(), self.cli.daily_limit.to_string()),
+			("HTTP_X_RATELIMIT_ACCOUNT_SUCCESS_REMAINING".into(), remaining.to_string()),
+			("HTTP_X_RATELIMIT_ACCOUNT_SUCCESS_RESET".into(), seconds_til_reset.to_string()),
+		];
+
+		response
+	}
+
+	fn generate_id(&mut self, kind: &str) -> String {
+		let id = self.next_id;
+		self.next_id += 1;
+		format!("mock-{kind}-{id:08}")
+	}
+}
+
+fn read_json<T: for<'de> Deserialize<'de>>(path: &Path) -> eyre::Result<T> {
+	let content = fs::read_to_string(path).wrap_err_with(|| format!("unable to read {}", path.display()))?;
+	serde_json::from_str(&content).wrap_err_with(|| format!("invalid fixture {}", path.display()))
+}
+
+fn header(name: &str, value: &str) -> Header {
+	Header::from_bytes(name.as_bytes(), value.as_bytes()).expect("valid header")
+}
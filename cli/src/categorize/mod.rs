@@ -0,0 +1,59 @@
+use color_eyre::eyre;
+use color_eyre::eyre::WrapErr;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use crate::matcher::{normal_transactions_mut, NormalTransaction, Transaction};
+
+pub mod rule;
+
+pub use rule::CategoryRule;
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct CategoryRules {
+	pub rules: Vec<CategoryRule>,
+}
+
+// The rules with their description patterns compiled once.
+#[derive(Debug, Default)]
+pub struct Categorizer {
+	rules: Vec<(CategoryRule, Option<Regex>)>,
+}
+
+impl Categorizer {
+	pub fn new(category_rules: CategoryRules) -> eyre::Result<Categorizer> {
+		let rules = category_rules.rules.into_iter()
+			.map(|rule| {
+				let description = rule.description.as_deref()
+					.map(Regex::new)
+					.transpose()
+					.wrap_err_with(|| format!("Invalid description pattern in category rule {}", rule.name))?;
+				Ok((rule, description))
+			})
+			.collect::<eyre::Result<_>>()?;
+
+		Ok(Categorizer { rules })
+	}
+
+	pub fn matching(&self, transaction: &NormalTransaction) -> Vec<&CategoryRule> {
+		self.rules.iter()
+			.filter(|(rule, description)| rule.matches(description.as_ref(), transaction))
+			.map(|(rule, _)| rule)
+			.collect()
+	}
+
+	// A payee from a rule takes precedence over the normalized one.
+	pub fn categorize(&self, transaction: &mut NormalTransaction) {
+		let rules = self.matching(transaction);
+		if let Some(payee) = rules.iter().find_map(|rule| rule.payee.as_ref()) {
+			transaction.payee = Some(payee.clone());
+		}
+		for rule in rules {
+			rule.apply(transaction);
+		}
+	}
+
+	// Transactions inside transfers and splits are categorized too, so they keep it when taken apart again.
+	pub fn categorize_all(&self, transactions: &mut [Transaction]) {
+		normal_transactions_mut(transactions).for_each(|transaction| self.categorize(transaction));
+	}
+}
@@ -0,0 +1,97 @@
+use chrono::NaiveDate;
+use regex::Regex;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use crate::matcher::NormalTransaction;
+use crate::nordigen::account::Account;
+
+// Every condition that is set has to hold for the rule to match, a rule without conditions matches everything.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CategoryRule {
+	pub name: String,
+
+	pub account: Option<String>,
+	pub min_amount: Option<Decimal>,
+	pub max_amount: Option<Decimal>,
+	pub counterparty: Option<String>,
+	pub description: Option<String>,
+	pub merchant_category_code: Option<String>,
+	pub date_from: Option<NaiveDate>,
+	pub date_until: Option<NaiveDate>,
+
+	pub category: Option<String>,
+	pub payee: Option<String>,
+	pub tags: Vec<String>,
+	pub memo: Option<String>,
+}
+
+impl CategoryRule {
+	pub fn matches(&self, description: Option<&Regex>, transaction: &NormalTransaction) -> bool {
+		if let Some(account) = &self.account {
+			let account = account.trim();
+			let Account { id, name, display_name, iban, bban, .. } = transaction.account.as_ref();
+			let is_account = id == account
+				|| [name, display_name].into_iter().flatten().any(|name| name.eq_ignore_ascii_case(account))
+				|| [iban, bban].into_iter().flatten().any(|number| normalize(number) == normalize(account));
+			if !is_account { return false; }
+		}
+
+		if self.min_amount.is_some_and(|min_amount| transaction.amount < min_amount) { return false; }
+		if self.max_amount.is_some_and(|max_amount| transaction.amount > max_amount) { return false; }
+
+		// The normalized payee counts as a name of the counterparty, for banks that only put it in the description.
+		if let Some(counterparty) = &self.counterparty {
+			let counterparty = counterparty.trim().to_lowercase();
+			let transaction_counterparty = transaction.counterparty.clone().unwrap_or_default();
+			let same_name = [&transaction_counterparty.name, &transaction.payee].into_iter()
+				.flatten()
+				.any(|name| name.to_lowercase().contains(&counterparty));
+			let same_number = [&transaction_counterparty.iban, &transaction_counterparty.bban].into_iter()
+				.flatten()
+				.any(|number| normalize(number) == normalize(&counterparty));
+			if !(same_name || same_number) { return false; }
+		}
+
+		if let Some(description) = description {
+			let matches = [&transaction.additional_info, &transaction.remittance_information].into_iter()
+				.flatten()
+				.any(|text| description.is_match(text));
+			if !matches { return false; }
+		}
+
+		if let Some(merchant_category_code) = &self.merchant_category_code {
+			if transaction.merchant_category_code.as_deref() != Some(merchant_category_code.trim()) { return false; }
+		}
+
+		if self.date_from.is_some_and(|date_from| transaction.date < date_from) { return false; }
+		if self.date_until.is_some_and(|date_until| transaction.date > date_until) { return false; }
+
+		true
+	}
+
+	// Earlier rules win, so only what is still missing is filled in. Tags are collected from every matching rule.
+	pub fn apply(&self, transaction: &mut NormalTransaction) {
+		if transaction.category.is_none() {
+			transaction.category.clone_from(&self.category);
+		}
+		if transaction.payee.is_none() {
+			transaction.payee.clone_from(&self.payee);
+		}
+		if transaction.memo.is_none() {
+			transaction.memo.clone_from(&self.memo);
+		}
+		for tag in &self.tags {
+			if !transaction.tags.contains(tag) {
+				transaction.tags.push(tag.clone());
+			}
+		}
+	}
+}
+
+fn normalize(account_number: &str) -> String {
+	account_number.chars()
+		.filter(|c| !c.is_whitespace())
+		.collect::<String>()
+		.to_uppercase()
+}
@@ -0,0 +1,133 @@
+use std::io::Write;
+use chrono::NaiveDate;
+use color_eyre::eyre;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use crate::export::account_name;
+use crate::matcher::Transaction;
+
+#[derive(Debug, Ord, PartialOrd, Eq, PartialEq, Serialize, Deserialize)]
+pub struct OutputFormat {
+	date: NaiveDate,
+	account_from: String,
+	account_to: Option<String>,
+	amount: Decimal,
+	currency: String,
+	description: String,
+	received_amount: Option<Decimal>,
+	received_currency: Option<String>,
+	fee: Option<Decimal>,
+	category: Option<String>,
+	payee: Option<String>,
+	tags: Option<String>,
+	memo: Option<String>,
+	balance: Option<Decimal>,
+	received_balance: Option<Decimal>,
+}
+
+impl OutputFormat {
+	fn records(transaction: Transaction) -> Vec<OutputFormat> {
+		match transaction {
+			Transaction::Normal(transaction) => vec![OutputFormat {
+				date: transaction.date,
+				account_from: account_name(&transaction.account),
+				account_to: None,
+				amount: transaction.amount,
+				currency: transaction.currency,
+				description: transaction.additional_info.unwrap_or_default(),
+				received_amount: None,
+				received_currency: None,
+				fee: None,
+				category: transaction.category,
+				payee: transaction.payee,
+				tags: tags(&transaction.tags),
+				memo: transaction.memo,
+				balance: transaction.balance,
+				received_balance: None,
+			}],
+			Transaction::Correction(transaction) => vec![OutputFormat {
+				date: transaction.date,
+				account_from: account_name(&transaction.account),
+				account_to: None,
+				amount: -transaction.amount,
+				currency: transaction.currency,
+				description: format!("reversal of: {}", transaction.additional_info.unwrap_or_default()),
+				received_amount: None,
+				received_currency: None,
+				fee: None,
+				category: transaction.category,
+				payee: transaction.payee,
+				tags: tags(&transaction.tags),
+				memo: transaction.memo,
+				balance: None,
+				received_balance: None,
+			}],
+			Transaction::Transfer(transaction) => vec![OutputFormat {
+				balance: transaction.balance_after(&transaction.from),
+				received_balance: transaction.balance_after(&transaction.to),
+				date: transaction.date,
+				account_from: account_name(&transaction.from),
+				account_to: Some(account_name(&transaction.to)),
+				amount: transaction.amount,
+				currency: transaction.currency,
+				description: format!("from: {} to: {}", transaction.from_additional_info.unwrap_or_default(), transaction.to_additional_info.unwrap_or_default()),
+				received_amount: Some(transaction.received_amount),
+				received_currency: Some(transaction.received_currency),
+				fee: transaction.fee,
+				category: None,
+				payee: None,
+				tags: None,
+				memo: None,
+			}],
+			Transaction::Split(transaction) => {
+				let account = account_name(&transaction.account);
+				let is_sender = transaction.amount.is_sign_negative();
+
+				transaction.legs.into_iter()
+					.map(|leg| {
+						let leg_account = account_name(&leg.account);
+						let (account_from, account_to, from_additional_info, to_additional_info, balance, received_balance) = if is_sender {
+							(account.clone(), leg_account, transaction.additional_info.clone(), leg.additional_info, transaction.source.balance, leg.balance)
+						} else {
+							(leg_account, account.clone(), leg.additional_info, transaction.additional_info.clone(), leg.balance, transaction.source.balance)
+						};
+
+						OutputFormat {
+							date: if is_sender { transaction.date } else { leg.date },
+							account_from,
+							account_to: Some(account_to),
+							amount: leg.amount.abs(),
+							currency: leg.currency.clone(),
+							description: format!("from: {} to: {}", from_additional_info.unwrap_or_default(), to_additional_info.unwrap_or_default()),
+							received_amount: Some(leg.amount.abs()),
+							received_currency: Some(leg.currency),
+							fee: None,
+							category: None,
+							payee: None,
+							tags: None,
+							memo: None,
+							balance,
+							received_balance,
+						}
+					})
+					.collect()
+			},
+		}
+	}
+}
+
+fn tags(tags: &[String]) -> Option<String> {
+	(!tags.is_empty()).then(|| tags.join(" "))
+}
+
+pub fn write(transactions: Vec<Transaction>, writer: impl Write) -> eyre::Result<()> {
+	let mut writer = csv::WriterBuilder::new().from_writer(writer);
+	for transaction in transactions {
+		for record in OutputFormat::records(transaction) {
+			writer.serialize(record)?;
+		}
+	}
+	writer.flush()?;
+
+	Ok(())
+}
@@ -0,0 +1,84 @@
+use std::io::Write;
+use color_eyre::eyre;
+use crate::export::account_name;
+use crate::matcher::{NormalTransaction, Transaction};
+
+static ASSETS: &str = "Assets";
+static FEES: &str = "Expenses:Fees";
+static UNCATEGORIZED_EXPENSES: &str = "Expenses:Uncategorized";
+static UNCATEGORIZED_INCOME: &str = "Income:Uncategorized";
+
+pub fn write(transactions: Vec<Transaction>, mut writer: impl Write) -> eyre::Result<()> {
+	for transaction in transactions {
+		match transaction {
+			Transaction::Normal(transaction) => {
+				let counter_account = counter_account(&transaction);
+
+				writeln!(writer, "{} {}", transaction.date, payee(&transaction))?;
+				write_comments(&mut writer, &transaction)?;
+				writeln!(writer, "    {ASSETS}:{}  {} {}", account_name(&transaction.account), transaction.amount, transaction.currency)?;
+				writeln!(writer, "    {counter_account}")?;
+			},
+			Transaction::Correction(transaction) => {
+				let counter_account = counter_account(&transaction);
+
+				writeln!(writer, "{} Reversal of: {}", transaction.date, payee(&transaction))?;
+				write_comments(&mut writer, &transaction)?;
+				writeln!(writer, "    {ASSETS}:{}  {} {}", account_name(&transaction.account), -transaction.amount, transaction.currency)?;
+				writeln!(writer, "    {counter_account}")?;
+			},
+			Transaction::Transfer(transaction) => {
+				let description = [transaction.from_additional_info, transaction.to_additional_info].into_iter()
+					.flatten()
+					.collect::<Vec<_>>()
+					.join(" / ");
+
+				writeln!(writer, "{} {description}", transaction.date)?;
+				if transaction.received_currency == transaction.currency {
+					writeln!(writer, "    {ASSETS}:{}  {} {}", account_name(&transaction.to), transaction.received_amount, transaction.received_currency)?;
+				} else {
+					writeln!(writer, "    {ASSETS}:{}  {} {} @@ {} {}", account_name(&transaction.to), transaction.received_amount, transaction.received_currency, transaction.amount, transaction.currency)?;
+				}
+				if let Some(fee) = transaction.fee {
+					writeln!(writer, "    {FEES}  {fee} {}", transaction.currency)?;
+				}
+				writeln!(writer, "    {ASSETS}:{}  {} {}", account_name(&transaction.from), -transaction.amount, transaction.currency)?;
+			},
+			Transaction::Split(transaction) => {
+				writeln!(writer, "{} {}", transaction.date, transaction.additional_info.unwrap_or_default())?;
+				writeln!(writer, "    {ASSETS}:{}  {} {}", account_name(&transaction.account), transaction.amount, transaction.currency)?;
+				for leg in transaction.legs {
+					writeln!(writer, "    {ASSETS}:{}  {} {}", account_name(&leg.account), leg.amount, leg.currency)?;
+				}
+			},
+		}
+		writeln!(writer)?;
+	}
+
+	Ok(())
+}
+
+fn payee(transaction: &NormalTransaction) -> String {
+	transaction.payee.clone()
+		.or_else(|| transaction.additional_info.clone())
+		.unwrap_or_default()
+}
+
+fn write_comments(writer: &mut impl Write, transaction: &NormalTransaction) -> eyre::Result<()> {
+	if let Some(memo) = &transaction.memo {
+		writeln!(writer, "    ; {memo}")?;
+	}
+	if !transaction.tags.is_empty() {
+		writeln!(writer, "    ; :{}:", transaction.tags.join(":"))?;
+	}
+
+	Ok(())
+}
+
+fn counter_account(transaction: &NormalTransaction) -> String {
+	match &transaction.category {
+		Some(category) => category.clone(),
+		None if transaction.amount.is_sign_negative() => UNCATEGORIZED_EXPENSES.to_string(),
+		None => UNCATEGORIZED_INCOME.to_string(),
+	}
+}
@@ -0,0 +1,26 @@
+use std::io::Write;
+use clap::ValueEnum;
+use color_eyre::eyre;
+use crate::matcher::Transaction;
+use crate::nordigen::account::Account;
+
+pub mod csv;
+pub mod ledger;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+	Csv,
+	Ledger,
+}
+
+pub fn write(format: ExportFormat, transactions: Vec<Transaction>, writer: impl Write) -> eyre::Result<()> {
+	match format {
+		ExportFormat::Csv => csv::write(transactions, writer),
+		ExportFormat::Ledger => ledger::write(transactions, writer),
+	}
+}
+
+pub fn account_name(account: &Account) -> String {
+	let Account { bban, iban, name, display_name, .. } = account.clone();
+	name.or(display_name).or(bban).or(iban).unwrap_or("unknown".into())
+}
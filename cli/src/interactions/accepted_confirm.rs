@@ -7,7 +7,7 @@ pub struct AcceptedConfirm<'a> {
 }
 
 impl<'a> AcceptedConfirm<'a> {
-	pub fn new(institution: &'a Institution) -> AcceptedConfirm {
+	pub fn new(institution: &'a Institution) -> AcceptedConfirm<'a> {
 		AcceptedConfirm {
 			institution
 		}
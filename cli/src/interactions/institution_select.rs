@@ -6,7 +6,7 @@ pub struct InstitutionSelect {
 	available_institutions: Vec<Institution>,
 }
 
-impl<'a> InstitutionSelect {
+impl InstitutionSelect {
 	pub fn new(available_institutions: Vec<Institution>) -> InstitutionSelect {
 		InstitutionSelect {
 			available_institutions,
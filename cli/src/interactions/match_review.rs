@@ -0,0 +1,134 @@
+use std::fmt::{Display, Formatter};
+use color_eyre::eyre;
+use inquire::Select;
+use crate::interactions::transaction_details::side_by_side;
+use crate::matcher::NormalTransaction;
+use crate::matcher::score::Score;
+use crate::matcher::split::Split;
+
+pub enum Review {
+	Picked(usize),
+	Rejected,
+	Deferred,
+	Back,
+}
+
+pub struct Progress {
+	pub position: usize,
+	pub total: usize,
+	pub answered: usize,
+}
+
+impl Display for Progress {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		write!(f, "Transaction {} of {}, {} questions answered so far", self.position, self.total, self.answered)
+	}
+}
+
+enum ReviewOption {
+	Candidate(usize, String),
+	Reject,
+	Defer,
+	Back,
+}
+
+impl Display for ReviewOption {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		match self {
+			ReviewOption::Candidate(index, description) => write!(f, "{}. {description}", index + 1),
+			ReviewOption::Reject => write!(f, "None of these, don't ask about them again"),
+			ReviewOption::Defer => write!(f, "Not sure, ask again next time"),
+			ReviewOption::Back => write!(f, "Go back to the previous question"),
+		}
+	}
+}
+
+fn prompt(message: &str, candidates: Vec<String>, can_go_back: bool) -> eyre::Result<Review> {
+	let mut options = candidates.into_iter()
+		.enumerate()
+		.map(|(index, description)| ReviewOption::Candidate(index, description))
+		.collect::<Vec<_>>();
+	options.push(ReviewOption::Reject);
+	options.push(ReviewOption::Defer);
+	if can_go_back {
+		options.push(ReviewOption::Back);
+	}
+
+	let answer = Select::new(message, options)
+		.with_help_message("↑↓ to move, enter to select, type to filter, esc to defer")
+		.prompt_skippable()?;
+
+	Ok(match answer {
+		Some(ReviewOption::Candidate(index, _)) => Review::Picked(index),
+		Some(ReviewOption::Reject) => Review::Rejected,
+		Some(ReviewOption::Defer) | None => Review::Deferred,
+		Some(ReviewOption::Back) => Review::Back,
+	})
+}
+
+pub struct MatchReview<'a> {
+	progress: Progress,
+	target: &'a NormalTransaction,
+	candidates: Vec<(&'a NormalTransaction, Score)>,
+	can_go_back: bool,
+}
+
+impl<'a> MatchReview<'a> {
+	pub fn new(progress: Progress, target: &'a NormalTransaction, candidates: Vec<(&'a NormalTransaction, Score)>, can_go_back: bool) -> MatchReview<'a> {
+		MatchReview {
+			progress,
+			target,
+			candidates,
+			can_go_back,
+		}
+	}
+
+	pub fn prompt(self) -> eyre::Result<Review> {
+		eprintln!();
+		eprintln!("{}", self.progress);
+		eprintln!("Is this transaction part of a transfer?");
+
+		let mut columns = vec![("This transaction".to_string(), self.target, None)];
+		for (index, (candidate, score)) in self.candidates.iter().enumerate() {
+			columns.push((format!("Candidate {}", index + 1), *candidate, Some(*score)));
+		}
+		eprintln!("{}", side_by_side(&columns));
+
+		let candidates = self.candidates.iter()
+			.map(|(candidate, score)| format!("[{score}] {candidate}"))
+			.collect();
+
+		prompt("Which is the other half of the transfer?", candidates, self.can_go_back)
+	}
+}
+
+pub struct SplitReview<'a, 'b> {
+	progress: Progress,
+	target: &'a NormalTransaction,
+	splits: &'a [Split<'b>],
+	can_go_back: bool,
+}
+
+impl<'a, 'b> SplitReview<'a, 'b> {
+	pub fn new(progress: Progress, target: &'a NormalTransaction, splits: &'a [Split<'b>], can_go_back: bool) -> SplitReview<'a, 'b> {
+		SplitReview {
+			progress,
+			target,
+			splits,
+			can_go_back,
+		}
+	}
+
+	pub fn prompt(self) -> eyre::Result<Review> {
+		eprintln!();
+		eprintln!("{}", self.progress);
+		eprintln!("Was this transaction split across several transactions?");
+		eprintln!("{}", side_by_side(&[("This transaction".to_string(), self.target, None)]));
+
+		let candidates = self.splits.iter()
+			.map(|split| split.to_string())
+			.collect();
+
+		prompt("Which transactions make up the other side of the transfer?", candidates, self.can_go_back)
+	}
+}
@@ -2,8 +2,16 @@ pub use client_credentials_input::ClientCredentialsInput;
 pub use institution_select::InstitutionSelect;
 pub use reuse_confirm::ReuseConfirm;
 pub use accepted_confirm::AcceptedConfirm;
+pub use save_rule_confirm::SaveRuleConfirm;
+pub use match_review::{MatchReview, Progress, Review, SplitReview};
+pub use review_summary::{ReviewSummary, SummaryAction};
+pub use transaction_details::side_by_side;
 
 mod client_credentials_input;
 mod institution_select;
 mod reuse_confirm;
 mod accepted_confirm;
+mod save_rule_confirm;
+mod match_review;
+mod review_summary;
+mod transaction_details;
@@ -7,7 +7,7 @@ pub struct ReuseConfirm<'a> {
 }
 
 impl<'a> ReuseConfirm<'a> {
-	pub fn new(selected: &'a [Institution]) -> ReuseConfirm {
+	pub fn new(selected: &'a [Institution]) -> ReuseConfirm<'a> {
 		ReuseConfirm {
 			selected
 		}
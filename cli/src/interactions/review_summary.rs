@@ -0,0 +1,44 @@
+use std::fmt::{Display, Formatter};
+use color_eyre::eyre;
+use inquire::Select;
+
+pub enum SummaryAction {
+	Confirm,
+	Back,
+	StartOver,
+}
+
+impl Display for SummaryAction {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		match self {
+			SummaryAction::Confirm => write!(f, "Looks good"),
+			SummaryAction::Back => write!(f, "Go back to the last question"),
+			SummaryAction::StartOver => write!(f, "Start the review over"),
+		}
+	}
+}
+
+pub struct ReviewSummary<'a> {
+	reviewed: &'a [String],
+}
+
+impl<'a> ReviewSummary<'a> {
+	pub fn new(reviewed: &'a [String]) -> ReviewSummary<'a> {
+		ReviewSummary {
+			reviewed
+		}
+	}
+
+	pub fn prompt(self) -> eyre::Result<SummaryAction> {
+		eprintln!();
+		eprintln!("You answered {} questions:", self.reviewed.len());
+		for reviewed in self.reviewed {
+			eprintln!("  {reviewed}");
+		}
+
+		let answer = Select::new("Apply these answers?", vec![SummaryAction::Confirm, SummaryAction::Back, SummaryAction::StartOver])
+			.prompt_skippable()?;
+
+		Ok(answer.unwrap_or(SummaryAction::Confirm))
+	}
+}
@@ -0,0 +1,34 @@
+use color_eyre::eyre;
+use inquire::{Confirm, Text};
+use crate::matcher::rules::TransferRule;
+
+pub struct SaveRuleConfirm {
+	rule: TransferRule,
+}
+
+impl SaveRuleConfirm {
+	pub fn new(rule: TransferRule) -> SaveRuleConfirm {
+		SaveRuleConfirm {
+			rule
+		}
+	}
+
+	pub fn prompt(mut self) -> eyre::Result<TransferRule> {
+		eprintln!("You have matched transfers {} several times now", self.rule.label);
+
+		self.rule.enabled = Confirm::new("Always treat these as transfers without asking?")
+			.with_default(true)
+			.with_help_message("Answering no stops this suggestion from coming back")
+			.prompt()?;
+
+		if self.rule.enabled {
+			let description_contains = Text::new("Only when a description contains")
+				.with_initial_value(self.rule.description_contains.as_deref().unwrap_or_default())
+				.with_help_message("Leave empty to match any description")
+				.prompt()?;
+			self.rule.description_contains = Some(description_contains.trim().to_string()).filter(|text| !text.is_empty());
+		}
+
+		Ok(self.rule)
+	}
+}
@@ -0,0 +1,91 @@
+use std::mem;
+use crate::matcher::NormalTransaction;
+use crate::matcher::score::Score;
+
+static COLUMN_WIDTH: usize = 34;
+static FIELDS: [&str; 7] = ["Date", "Account", "Amount", "Counterparty", "Information", "Remittance", "Score"];
+
+// Fields of several transactions next to each other, one column per transaction.
+// Values too long for a column go on over several lines, so the full remittance information can be read.
+pub fn side_by_side(columns: &[(String, &NormalTransaction, Option<Score>)]) -> String {
+	let titles = columns.iter().map(|(title, _, _)| wrap(title)).collect::<Vec<_>>();
+	let mut table = row("", &titles);
+
+	for name in FIELDS {
+		let values = columns.iter()
+			.map(|(_, transaction, score)| field(name, transaction, *score))
+			.collect::<Vec<_>>();
+		if values.iter().all(String::is_empty) { continue; }
+
+		table += &row(name, &values.iter().map(|value| wrap(value)).collect::<Vec<_>>());
+	}
+
+	table
+}
+
+fn row(name: &str, cells: &[Vec<String>]) -> String {
+	let height = cells.iter().map(Vec::len).max().unwrap_or(1);
+
+	let mut row = String::new();
+	for line in 0..height {
+		row += &format!("{:<14}", if line == 0 { name } else { "" });
+		for cell in cells {
+			row += &format!("{:<COLUMN_WIDTH$} ", cell.get(line).map_or("", String::as_str));
+		}
+		row += "\n";
+	}
+
+	row
+}
+
+fn field(name: &str, transaction: &NormalTransaction, score: Option<Score>) -> String {
+	match name {
+		"Date" => transaction.date.to_string(),
+		"Account" => transaction.account.to_string(),
+		"Amount" => format!("{} {}", transaction.amount, transaction.currency),
+		"Counterparty" => counterparty(transaction),
+		"Information" => transaction.additional_info.clone().unwrap_or_default(),
+		"Remittance" => transaction.remittance_information.clone().unwrap_or_default(),
+		"Score" => score.map(|score| score.to_string()).unwrap_or_default(),
+		_ => String::new(),
+	}
+}
+
+fn counterparty(transaction: &NormalTransaction) -> String {
+	let Some(counterparty) = &transaction.counterparty else { return String::new() };
+
+	[&counterparty.name, &counterparty.iban, &counterparty.bban].into_iter()
+		.flatten()
+		.cloned()
+		.collect::<Vec<_>>()
+		.join(" ")
+}
+
+// Words are kept whole unless a single one is longer than a line, like a long reference.
+fn wrap(text: &str) -> Vec<String> {
+	let mut lines = vec![];
+	let mut line = String::new();
+
+	for word in text.split_whitespace() {
+		let mut word = word.chars().collect::<Vec<_>>();
+		let line_length = line.chars().count();
+		if line_length > 0 && line_length + 1 + word.len() <= COLUMN_WIDTH {
+			line.push(' ');
+			line.extend(word);
+			continue;
+		}
+
+		if line_length > 0 {
+			lines.push(mem::take(&mut line));
+		}
+		while word.len() > COLUMN_WIDTH {
+			lines.push(word.drain(..COLUMN_WIDTH).collect());
+		}
+		line = word.into_iter().collect();
+	}
+
+	if !line.is_empty() || lines.is_empty() {
+		lines.push(line);
+	}
+	lines
+}
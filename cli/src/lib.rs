@@ -0,0 +1,11 @@
+pub mod nordigen;
+pub mod interactions;
+pub mod matcher;
+pub mod export;
+pub mod categorize;
+pub mod payees;
+pub mod report;
+#[cfg(feature = "tui")]
+pub mod tui;
+
+pub static APP_NAME: &str = "njord";
@@ -1,83 +1,404 @@
-mod nordigen;
-mod interactions;
-mod matcher;
-
 use std::io::stdout;
-use std::ops::Deref;
-use chrono::NaiveDate;
+use std::path::PathBuf;
+use chrono::{Local, NaiveDate};
+use clap::{Parser, Subcommand};
 use color_eyre::eyre;
-use once_cell::sync::Lazy;
-use reqwest::blocking::Client;
-use reqwest::header::{HeaderMap, HeaderValue};
-use rust_decimal::Decimal;
-use serde::{Deserialize, Serialize};
-use crate::matcher::{match_transactions, Transaction};
-use crate::nordigen::account::Account;
-use crate::nordigen::get_raw_transactions;
-
-pub static APP_NAME: &'static str = "njord";
-pub static HTTP_CLIENT: Lazy<Client> = Lazy::new(|| {
-	let mut default_headers = HeaderMap::new();
-	default_headers.insert("Accept", HeaderValue::from_static("application/json"));
-
-	Client::builder()
-		.default_headers(default_headers)
-		.build()
-		.expect("unable to build HTTP client")
-});
+use color_eyre::eyre::eyre;
+use njord::{APP_NAME, export, report};
+use njord::categorize::{Categorizer, CategoryRules};
+use njord::export::ExportFormat;
+use njord::payees::{PayeeAliases, PayeeNormalizer};
+use njord::report::{balances, budget, recurring, ReportFormat};
+use njord::report::net_worth::{self, NetWorthHistory, NetWorthRates, Snapshot};
+use njord::matcher::{explain, match_transactions, Transaction};
+use njord::matcher::unmatched::UnmatchedStore;
+use njord::matcher::decisions::Decisions;
+use njord::nordigen::{FetchOptions, get_balances, get_raw_transactions};
+use njord::nordigen::config::Config;
+
+#[derive(Debug, Parser)]
+#[command(version, about)]
+struct Cli {
+	/// Fetch transactions even if it spends the last API call of the day for an account
+	#[arg(long)]
+	force: bool,
+
+	/// Reuse the previously selected institutions without asking
+	#[arg(long)]
+	reuse: bool,
+
+	/// Nordigen API base URL, overrides the one in the config
+	#[arg(long)]
+	base_url: Option<String>,
+
+	/// Record every request and response, with secrets and account numbers redacted, to this directory
+	#[arg(long, value_name = "DIR", conflicts_with = "replay")]
+	record: Option<PathBuf>,
+
+	/// Serve requests from a recording made with --record instead of the network
+	#[arg(long, value_name = "DIR")]
+	replay: Option<PathBuf>,
+
+	/// Output format
+	#[arg(long, value_enum, default_value_t = ExportFormat::Csv)]
+	format: ExportFormat,
+
+	/// Review the matched transactions in a full screen terminal UI instead of answering questions one by one
+	#[cfg(feature = "tui")]
+	#[arg(long)]
+	tui: bool,
+
+	#[command(subcommand)]
+	command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+	/// Match transactions into transfers, the same as running without a command
+	Match {
+		/// Instead of matching, list every candidate for this transaction with the reason it is accepted, rejected or asked about
+		#[arg(long, value_name = "TRANSACTION_ID")]
+		explain: Option<String>,
+
+		/// With --explain, also list the transactions in the same account, going the same way or clearly unrelated
+		#[arg(long, requires = "explain")]
+		all: bool,
+	},
+
+	/// Summarize income, expenses and net per month, by account and by category, leaving out transfers
+	Report {
+		/// Output format of the report
+		#[arg(long, value_enum, default_value_t = ReportFormat::Table)]
+		output: ReportFormat,
+	},
+
+	/// Compare the spending per category with the budgets in config.ron, month by month
+	Budget {
+		/// Only show this month, like 2023-04
+		#[arg(long, value_parser = budget::parse_month)]
+		month: Option<String>,
+
+		/// Output format of the budgets
+		#[arg(long, value_enum, default_value_t = ReportFormat::Table)]
+		output: ReportFormat,
+	},
+
+	/// List the charges and payments that come back every week, month or year, with when the next one is expected.
+	/// Only the transactions the bank returns are looked at, often 90 days, so yearly ones need a bank that returns more than a year
+	Recurring {
+		/// Output format of the list
+		#[arg(long, value_enum, default_value_t = ReportFormat::Table)]
+		output: ReportFormat,
+	},
+
+	/// Show the balance of each account after its latest transaction
+	Balances {
+		/// Show the balance at the end of every day instead
+		#[arg(long)]
+		history: bool,
+
+		/// First day of the history, defaults to the day of the first transaction
+		#[arg(long, requires = "history")]
+		from: Option<NaiveDate>,
+
+		/// Last day of the history, defaults to the day of the last transaction
+		#[arg(long, requires = "history")]
+		until: Option<NaiveDate>,
+
+		/// Output format of the balances
+		#[arg(long, value_enum, default_value_t = ReportFormat::Table)]
+		output: ReportFormat,
+	},
+
+	/// Fetch the balance of every linked account, add them up in the base currency of rates.ron and store the total for today
+	NetWorth {
+		/// Show the stored totals instead of fetching a new one
+		#[arg(long)]
+		history: bool,
+
+		/// Output format of the net worth
+		#[arg(long, value_enum, default_value_t = ReportFormat::Table)]
+		output: ReportFormat,
+	},
+
+	/// Work with the category rules in rules.ron
+	Rules {
+		#[command(subcommand)]
+		command: RulesCommand,
+	},
+
+	/// Manage the remembered answers to transfer match questions
+	Decisions {
+		#[command(subcommand)]
+		command: DecisionsCommand,
+	},
+}
+
+#[derive(Debug, Subcommand)]
+enum DecisionsCommand {
+	/// List remembered decisions and transfer rules
+	List,
+
+	/// Forget the decisions involving a transaction, so it is asked about again
+	Revoke {
+		/// Nordigen transaction ID
+		#[arg(required_unless_present = "all")]
+		transaction_id: Option<String>,
+
+		/// Forget all decisions
+		#[arg(long, conflicts_with = "transaction_id")]
+		all: bool,
+	},
+
+	/// Forget a transfer rule, by its number in the list
+	ForgetRule {
+		number: usize,
+	},
+}
+
+#[derive(Debug, Subcommand)]
+enum RulesCommand {
+	/// Apply the category rules to the transactions the next export would get, and the unmatched ones kept from earlier runs, and show the outcome
+	Test {
+		/// Show the transactions no rule matches instead
+		#[arg(long)]
+		unmatched: bool,
+	},
+}
+
+enum Mode {
+	Export,
+	Explain { transaction_id: String, all: bool },
+	Report(ReportFormat),
+	Recurring(ReportFormat),
+	Budget { month: Option<String>, output: ReportFormat },
+	Balances { history: bool, from: Option<NaiveDate>, until: Option<NaiveDate>, output: ReportFormat },
+}
+
+impl Mode {
+	// Only an export moves on to the next transactions, the other commands look at them and leave them for it.
+	fn is_read_only(&self) -> bool {
+		!matches!(self, Mode::Export)
+	}
+}
 
 fn main() -> eyre::Result<()> {
-	let raw_transactions = get_raw_transactions()?;
-	let matched_transactions = match_transactions(&raw_transactions)?;
+	let cli = Cli::parse();
+
+	let mode = match cli.command {
+		Some(Command::Decisions { command }) => return manage_decisions(command),
+		Some(Command::Rules { command }) => {
+			return manage_rules(command, FetchOptions {
+				force: cli.force,
+				reuse_institutions: cli.reuse,
+				base_url: cli.base_url,
+				record: cli.record,
+				replay: cli.replay,
+				read_only: true,
+			});
+		},
+		Some(Command::NetWorth { history: true, output }) => {
+			let history = confy::load::<NetWorthHistory>(APP_NAME, Some("net_worth"))?;
+			return net_worth::write_history(output, &history, stdout());
+		},
+		Some(Command::NetWorth { history: false, output }) => {
+			return take_net_worth_snapshot(FetchOptions {
+				force: cli.force,
+				reuse_institutions: cli.reuse,
+				base_url: cli.base_url,
+				record: cli.record,
+				replay: cli.replay,
+				read_only: false,
+			}, output);
+		},
+		Some(Command::Match { explain: Some(transaction_id), all }) => Mode::Explain { transaction_id, all },
+		Some(Command::Report { output }) => Mode::Report(output),
+		Some(Command::Recurring { output }) => Mode::Recurring(output),
+		Some(Command::Budget { month, output }) => Mode::Budget { month, output },
+		Some(Command::Balances { history, from, until, output }) => Mode::Balances { history, from, until, output },
+		Some(Command::Match { explain: None, .. }) | None => Mode::Export,
+	};
 
+	let config = confy::load::<Config>(APP_NAME, Some("config"))?;
+	let categorizer = Categorizer::new(confy::load::<CategoryRules>(APP_NAME, Some("rules"))?)?;
+	let payee_normalizer = PayeeNormalizer::new(confy::load::<PayeeAliases>(APP_NAME, Some("payees"))?);
+	let matcher_config = config.matcher.clone();
+	let budgets = config.budgets.clone();
 
-	let mut writer = csv::WriterBuilder::new().from_writer(stdout());
-	for matched_transaction in matched_transactions {
-		let record = OutputFormat::from(matched_transaction);
-		writer.serialize(record)?;
+	let read_only = mode.is_read_only();
+	let raw_transactions = get_raw_transactions(config, &FetchOptions {
+		force: cli.force,
+		reuse_institutions: cli.reuse,
+		base_url: cli.base_url,
+		record: cli.record,
+		replay: cli.replay.clone(),
+		read_only,
+	})?;
+
+	if let Mode::Balances { history, from, until, output } = mode {
+		let rows = if history {
+			balances::history(&raw_transactions, from, until)
+		} else {
+			balances::latest(&raw_transactions)
+		};
+		return balances::write(output, &rows, history, stdout());
 	}
 
-	Ok(())
+	let mut decisions = confy::load::<Decisions>(APP_NAME, Some("decisions"))?;
+	// A report covers everything fetched, earlier exports only matter for what is exported next.
+	let previously_exported = match mode {
+		Mode::Report(_) | Mode::Recurring(_) | Mode::Budget { .. } => vec![],
+		_ => confy::load::<UnmatchedStore>(APP_NAME, Some("unmatched"))?.into_transactions(&raw_transactions),
+	};
+
+	if let Mode::Explain { transaction_id, all } = &mode {
+		print!("{}", explain::explain(&raw_transactions, previously_exported, &matcher_config, &decisions, transaction_id, *all)?);
+		return Ok(());
+	}
+
+	// Questions are left for the next export, a command that only looks at the transactions should not change what is remembered.
+	#[cfg(feature = "tui")]
+	let interactive = !cli.tui && !read_only;
+	#[cfg(not(feature = "tui"))]
+	let interactive = !read_only;
+
+	let mut matched_transactions = match_transactions(&raw_transactions, previously_exported, &matcher_config, &mut decisions, interactive)?;
+	payee_normalizer.normalize_all(&mut matched_transactions);
+	categorizer.categorize_all(&mut matched_transactions);
+	#[cfg(feature = "tui")]
+	let matched_transactions = if cli.tui && !read_only {
+		njord::tui::review(matched_transactions, &mut decisions)?
+	} else {
+		matched_transactions
+	};
+	if cli.replay.is_none() && !read_only {
+		confy::store(APP_NAME, Some("decisions"), decisions)?;
+	}
+	match mode {
+		Mode::Report(format) => return report::write(format, &report::aggregate(&matched_transactions), stdout()),
+		Mode::Recurring(format) => return recurring::write(format, &recurring::detect(&matched_transactions, Local::now().date_naive()), stdout()),
+		Mode::Budget { month, output } => {
+			let rows = budget::track(&budgets, &matched_transactions, month.as_deref());
+			for row in rows.iter().filter(|row| row.is_overspent()) {
+				eprintln!("Over budget for {} in {}, by {} {}", row.category, row.month, -row.remaining, row.currency);
+			}
+			return budget::write(output, &rows, stdout());
+		},
+		_ => {},
+	}
+	if cli.replay.is_none() {
+		confy::store(APP_NAME, Some("unmatched"), UnmatchedStore::from_transactions(&matched_transactions, matcher_config.unmatched_retention_days))?;
+	}
+
+	let new_transactions = matched_transactions.into_iter()
+		.filter(|transaction| !matches!(transaction, Transaction::Normal(transaction) if transaction.previously_exported))
+		.collect();
+
+	export::write(cli.format, new_transactions, stdout())
 }
 
-#[derive(Debug, Ord, PartialOrd, Eq, PartialEq, Serialize, Deserialize)]
-struct OutputFormat {
-	date: NaiveDate,
-	account_from: String,
-	account_to: Option<String>,
-	amount: Decimal,
-	currency: String,
-	description: String,
+fn take_net_worth_snapshot(options: FetchOptions, output: ReportFormat) -> eyre::Result<()> {
+	let config = confy::load::<Config>(APP_NAME, Some("config"))?;
+	let rates = confy::load::<NetWorthRates>(APP_NAME, Some("rates"))?;
+	let account_balances = get_balances(config, &options)?;
+	let snapshot = Snapshot::take(Local::now().date_naive(), &account_balances, &rates)?;
+
+	if options.replay.is_none() {
+		let mut history = confy::load::<NetWorthHistory>(APP_NAME, Some("net_worth"))?;
+		history.add(snapshot.clone());
+		confy::store(APP_NAME, Some("net_worth"), history)?;
+	}
+
+	net_worth::write_snapshot(output, &snapshot, stdout())
+}
+
+fn manage_decisions(command: DecisionsCommand) -> eyre::Result<()> {
+	let mut decisions = confy::load::<Decisions>(APP_NAME, Some("decisions"))?;
+
+	match command {
+		DecisionsCommand::List => {
+			for decision in &decisions.decisions {
+				println!("{decision}");
+			}
+			for (index, rule) in decisions.rules.iter().enumerate() {
+				println!("Rule {}: {rule}", index + 1);
+			}
+		},
+		DecisionsCommand::Revoke { all: true, .. } => {
+			eprintln!("Forgot {} decisions", decisions.decisions.len());
+			decisions.decisions.clear();
+			confy::store(APP_NAME, Some("decisions"), decisions)?;
+		},
+		DecisionsCommand::Revoke { transaction_id, .. } => {
+			let transaction_id = transaction_id.unwrap_or_default();
+			let revoked = decisions.revoke(&transaction_id);
+			eprintln!("Forgot {revoked} decisions involving {transaction_id}");
+			confy::store(APP_NAME, Some("decisions"), decisions)?;
+		},
+		DecisionsCommand::ForgetRule { number } => {
+			if number == 0 || number > decisions.rules.len() {
+				return Err(eyre!("There is no rule {number}, see `njord decisions list`"));
+			}
+			let rule = decisions.rules.remove(number - 1);
+			eprintln!("Forgot rule {rule}");
+			confy::store(APP_NAME, Some("decisions"), decisions)?;
+		},
+	}
+
+	Ok(())
 }
 
-impl From<Transaction> for OutputFormat {
-	fn from(transaction: Transaction) -> Self {
-		match transaction {
-			Transaction::Normal(transaction) => OutputFormat {
-				date: transaction.date,
-				account_from: {
-					let Account { bban, iban, name, display_name, .. } = transaction.account.deref().clone();
-					name.or(display_name).or(bban).or(iban).unwrap_or("unknown".into())
-				},
-				account_to: None,
-				amount: transaction.amount,
-				currency: transaction.currency,
-				description: transaction.additional_info.unwrap_or_default(),
-			},
-			Transaction::Transfer(transaction) => OutputFormat {
-				date: transaction.date,
-				account_from: {
-					let Account { bban, iban, name, display_name, .. } = transaction.from.deref().clone();
-					name.or(display_name).or(bban).or(iban).unwrap_or("unknown".into())
-				},
-				account_to: {
-					let Account { bban, iban, name, display_name, .. } = transaction.to.deref().clone();
-					Some(name.or(display_name).or(bban).or(iban).unwrap_or("unknown".into()))
-				},
-				amount: transaction.amount,
-				currency: transaction.currency,
-				description: format!("from: {} to: {}", transaction.from_additional_info.unwrap_or_default(), transaction.to_additional_info.unwrap_or_default()),
-			},
-		}
+fn manage_rules(command: RulesCommand, options: FetchOptions) -> eyre::Result<()> {
+	let config = confy::load::<Config>(APP_NAME, Some("config"))?;
+	let categorizer = Categorizer::new(confy::load::<CategoryRules>(APP_NAME, Some("rules"))?)?;
+	let payee_normalizer = PayeeNormalizer::new(confy::load::<PayeeAliases>(APP_NAME, Some("payees"))?);
+	let matcher_config = config.matcher.clone();
+	let mut decisions = confy::load::<Decisions>(APP_NAME, Some("decisions"))?;
+
+	// Matched the way an export would, without asking, as transfers are left uncategorized.
+	let raw_transactions = get_raw_transactions(config, &options)?;
+	let previously_exported = confy::load::<UnmatchedStore>(APP_NAME, Some("unmatched"))?.into_transactions(&raw_transactions);
+	let transactions = match_transactions(&raw_transactions, previously_exported, &matcher_config, &mut decisions, false)?.into_iter()
+		.filter_map(|transaction| match transaction {
+			Transaction::Normal(transaction) => Some(transaction),
+			_ => None,
+		})
+		.collect::<Vec<_>>();
+
+	match command {
+		RulesCommand::Test { unmatched } => {
+			let total = transactions.len();
+			let mut matched = 0;
+
+			for mut transaction in transactions {
+				// Starting over shows what the rules assign, rather than what was kept from an earlier run.
+				(transaction.category, transaction.payee, transaction.memo) = (None, None, None);
+				transaction.tags.clear();
+				payee_normalizer.normalize(&mut transaction);
+
+				let rules = categorizer.matching(&transaction);
+				if rules.is_empty() {
+					if unmatched {
+						println!("{transaction}");
+					}
+					continue;
+				}
+				matched += 1;
+				if unmatched { continue; }
+
+				println!("{transaction}");
+				println!("    rules: {}", rules.iter().map(|rule| rule.name.as_str()).collect::<Vec<_>>().join(", "));
+				categorizer.categorize(&mut transaction);
+				let assigned = [("category", transaction.category), ("payee", transaction.payee), ("memo", transaction.memo)].into_iter()
+					.filter_map(|(field, value)| Some(format!("{field}: {}", value?)))
+					.chain((!transaction.tags.is_empty()).then(|| format!("tags: {}", transaction.tags.join(" "))))
+					.collect::<Vec<_>>();
+				println!("    {}", assigned.join(", "));
+			}
+
+			eprintln!("{matched} of {total} fetched and kept transactions matched by a rule");
+		},
 	}
+
+	Ok(())
 }
@@ -0,0 +1,216 @@
+use std::collections::{HashMap, HashSet};
+use serde::{Deserialize, Serialize};
+use crate::matcher::config::MatcherConfig;
+use crate::matcher::decisions::{Decisions, Verdict};
+use crate::matcher::index::CandidateIndex;
+use crate::matcher::{evaluate_match, transfer_sides, NormalTransaction, Transaction};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MatchingStrategy {
+	// Every transaction in turn takes its best remaining candidate.
+	#[default]
+	Greedy,
+	// Pairs are assigned over all candidates at once, only clusters with more than one best assignment are asked about.
+	Optimal,
+}
+
+// Remembered pairs outweigh any combination of scores, so they are always part of the assignment.
+const REMEMBERED_WEIGHT: f64 = 1_000.0;
+const EPSILON: f64 = 1e-6;
+
+#[derive(Debug, Default)]
+pub struct Assignment {
+	partners: HashMap<usize, usize>,
+	ambiguous: HashSet<usize>,
+}
+
+impl Assignment {
+	// Candidates are the pairs that would be accepted without asking, connected pairs form a cluster that is solved on its own.
+	pub fn solve(config: &MatcherConfig, slots: &[Option<Transaction>], index: &CandidateIndex, decisions: &Decisions) -> Assignment {
+		let mut weights = HashMap::<(usize, usize), f64>::new();
+
+		for (position, slot) in slots.iter().enumerate() {
+			let Some(Transaction::Normal(target)) = slot else { continue };
+			if target.previously_exported { continue; }
+
+			for candidate_position in index.candidates(config, target) {
+				let Some(Transaction::Normal(candidate)) = &slots[candidate_position] else { continue };
+				let Some(weight) = weight(config, decisions, target, candidate) else { continue };
+
+				let key = (position.min(candidate_position), position.max(candidate_position));
+				let entry = weights.entry(key).or_insert(weight);
+				*entry = entry.max(weight);
+			}
+		}
+
+		let mut assignment = Assignment::default();
+		for cluster in clusters(&weights) {
+			let (senders, receivers): (Vec<usize>, Vec<usize>) = cluster.iter()
+				.partition(|position| matches!(&slots[**position], Some(Transaction::Normal(transaction)) if transaction.amount.is_sign_negative()));
+			let (rows, columns) = if senders.len() <= receivers.len() { (senders, receivers) } else { (receivers, senders) };
+
+			let edge = |row: usize, column: usize| weights.get(&(rows[row].min(columns[column]), rows[row].max(columns[column]))).copied();
+			// Pairing one more transaction always beats better scores on fewer pairs.
+			let heaviest = (0..rows.len())
+				.flat_map(|row| (0..columns.len()).filter_map(move |column| edge(row, column)))
+				.fold(0.0, f64::max);
+			let bonus = cluster.len() as f64 * heaviest + 1.0;
+			let matrix = (0..rows.len())
+				.map(|row| (0..columns.len()).map(|column| edge(row, column).map(|weight| weight + bonus)).collect())
+				.collect::<Vec<Vec<_>>>();
+
+			let (best, pairs) = best_assignment(&matrix);
+			let is_unique = pairs.iter().all(|&(row, column)| {
+				let mut without = matrix.clone();
+				without[row][column] = None;
+				best_assignment(&without).0 < best - EPSILON
+			});
+
+			if is_unique {
+				for (row, column) in pairs {
+					assignment.partners.insert(rows[row], columns[column]);
+					assignment.partners.insert(columns[column], rows[row]);
+				}
+			} else {
+				assignment.ambiguous.extend(cluster);
+			}
+		}
+
+		assignment
+	}
+
+	pub fn partner(&self, position: usize) -> Option<usize> {
+		self.partners.get(&position).copied()
+	}
+
+	// A transaction assigned to another one is not offered to anything else.
+	pub fn is_available(&self, candidate_position: usize, target_position: usize) -> bool {
+		self.partners.get(&candidate_position).is_none_or(|partner| *partner == target_position)
+	}
+
+	pub fn is_ambiguous(&self, position: usize) -> bool {
+		self.ambiguous.contains(&position)
+	}
+}
+
+fn weight(config: &MatcherConfig, decisions: &Decisions, target: &NormalTransaction, candidate: &NormalTransaction) -> Option<f64> {
+	let score = evaluate_match(config, target, candidate)?;
+
+	let is_remembered = match decisions.verdict(target, candidate) {
+		Some(Verdict::Accepted) => true,
+		Some(Verdict::Rejected) => return None,
+		None => {
+			let (from, to) = transfer_sides(target, candidate);
+			decisions.rule_for(from, to).is_some()
+		},
+	};
+
+	if is_remembered {
+		Some(REMEMBERED_WEIGHT + score.total)
+	} else if score.total >= config.auto_accept_score {
+		Some(score.total)
+	} else {
+		None
+	}
+}
+
+fn clusters(weights: &HashMap<(usize, usize), f64>) -> Vec<Vec<usize>> {
+	let mut neighbours = HashMap::<usize, Vec<usize>>::new();
+	for &(a, b) in weights.keys() {
+		neighbours.entry(a).or_default().push(b);
+		neighbours.entry(b).or_default().push(a);
+	}
+
+	let mut starts = neighbours.keys().copied().collect::<Vec<_>>();
+	starts.sort_unstable();
+
+	let mut visited = HashSet::new();
+	let mut clusters = vec![];
+	for start in starts {
+		if !visited.insert(start) { continue; }
+
+		let mut cluster = vec![start];
+		let mut next = 0;
+		while next < cluster.len() {
+			for &neighbour in &neighbours[&cluster[next]] {
+				if visited.insert(neighbour) {
+					cluster.push(neighbour);
+				}
+			}
+			next += 1;
+		}
+		cluster.sort_unstable();
+		clusters.push(cluster);
+	}
+
+	clusters
+}
+
+// The Hungarian algorithm on a matrix with no more rows than columns, a missing weight means the two can't be paired.
+// Returns the highest total weight and the (row, column) pairs making it up.
+fn best_assignment(matrix: &[Vec<Option<f64>>]) -> (f64, Vec<(usize, usize)>) {
+	let rows = matrix.len();
+	let columns = matrix.first().map_or(0, Vec::len);
+	let cost = |row: usize, column: usize| -matrix[row - 1][column - 1].unwrap_or(0.0);
+
+	// 1-based, with row and column 0 as the free starting point.
+	let mut row_potential = vec![0.0; rows + 1];
+	let mut column_potential = vec![0.0; columns + 1];
+	let mut column_row = vec![0; columns + 1];
+	let mut way = vec![0; columns + 1];
+
+	for row in 1..=rows {
+		column_row[0] = row;
+		let mut column = 0;
+		let mut slack = vec![f64::INFINITY; columns + 1];
+		let mut used = vec![false; columns + 1];
+
+		loop {
+			used[column] = true;
+			let current_row = column_row[column];
+			let mut delta = f64::INFINITY;
+			let mut next_column = 0;
+
+			for candidate_column in 1..=columns {
+				if used[candidate_column] { continue; }
+
+				let reduced = cost(current_row, candidate_column) - row_potential[current_row] - column_potential[candidate_column];
+				if reduced < slack[candidate_column] {
+					slack[candidate_column] = reduced;
+					way[candidate_column] = column;
+				}
+				if slack[candidate_column] < delta {
+					delta = slack[candidate_column];
+					next_column = candidate_column;
+				}
+			}
+
+			for candidate_column in 0..=columns {
+				if used[candidate_column] {
+					row_potential[column_row[candidate_column]] += delta;
+					column_potential[candidate_column] -= delta;
+				} else {
+					slack[candidate_column] -= delta;
+				}
+			}
+
+			column = next_column;
+			if column_row[column] == 0 { break; }
+		}
+
+		while column != 0 {
+			let previous_column = way[column];
+			column_row[column] = column_row[previous_column];
+			column = previous_column;
+		}
+	}
+
+	let pairs = (1..=columns)
+		.filter(|&column| column_row[column] != 0)
+		.map(|column| (column_row[column] - 1, column - 1))
+		.filter(|&(row, column)| matrix[row][column].is_some())
+		.collect::<Vec<_>>();
+	let total = pairs.iter().map(|&(row, column)| matrix[row][column].unwrap_or(0.0)).sum();
+
+	(total, pairs)
+}
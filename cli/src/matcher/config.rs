@@ -0,0 +1,47 @@
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use crate::matcher::assignment::MatchingStrategy;
+use crate::matcher::exchange::ExchangeRate;
+use crate::matcher::own_accounts::OwnAccount;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MatcherConfig {
+	pub matching_strategy: MatchingStrategy,
+	pub date_window_days: i64,
+	pub auto_accept_score: f64,
+	pub same_institution_weight: f64,
+	pub same_owner_weight: f64,
+	pub description_weight: f64,
+	pub exchange_rates: Vec<ExchangeRate>,
+	pub exchange_rate_tolerance: f64,
+	pub fee_tolerance_amount: Decimal,
+	pub fee_tolerance_percentage: f64,
+	pub split_transfers: bool,
+	pub max_split_legs: usize,
+	pub rule_suggestion_threshold: usize,
+	pub unmatched_retention_days: i64,
+	pub own_accounts: Vec<OwnAccount>,
+}
+
+impl Default for MatcherConfig {
+	fn default() -> Self {
+		MatcherConfig {
+			matching_strategy: MatchingStrategy::Greedy,
+			date_window_days: 5,
+			auto_accept_score: 1.0,
+			same_institution_weight: 0.0,
+			same_owner_weight: 0.0,
+			description_weight: 0.0,
+			exchange_rates: vec![],
+			exchange_rate_tolerance: 0.02,
+			fee_tolerance_amount: Decimal::ZERO,
+			fee_tolerance_percentage: 0.0,
+			split_transfers: false,
+			max_split_legs: 3,
+			rule_suggestion_threshold: 3,
+			unmatched_retention_days: 30,
+			own_accounts: vec![],
+		}
+	}
+}
@@ -0,0 +1,131 @@
+use std::collections::HashSet;
+use std::fmt::{Display, Formatter};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use crate::matcher::{NormalTransaction, transfer_sides};
+use crate::matcher::config::MatcherConfig;
+use crate::matcher::rules::TransferRule;
+use crate::matcher::score::words;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Verdict {
+	Accepted,
+	#[serde(alias = "Skipped")]
+	Rejected,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Decision {
+	pub transactions: (String, String),
+	#[serde(default)]
+	pub accounts: (String, String),
+	#[serde(default)]
+	pub descriptions: (Option<String>, Option<String>),
+	pub verdict: Verdict,
+	pub description: String,
+	pub decided_at: DateTime<Local>,
+}
+
+impl Decision {
+	pub fn involves(&self, transaction_id: &str) -> bool {
+		self.transactions.0 == transaction_id || self.transactions.1 == transaction_id
+	}
+
+	fn is_between(&self, a: &str, b: &str) -> bool {
+		(self.transactions.0 == a && self.transactions.1 == b) || (self.transactions.0 == b && self.transactions.1 == a)
+	}
+}
+
+impl Display for Decision {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{:?} {} <-> {} on {}\n    {}", self.verdict, self.transactions.0, self.transactions.1, self.decided_at.format("%Y-%m-%d %H:%M"), self.description)
+	}
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Decisions {
+	pub decisions: Vec<Decision>,
+	#[serde(default)]
+	pub rules: Vec<TransferRule>,
+}
+
+impl Decisions {
+	pub fn verdict(&self, a: &NormalTransaction, b: &NormalTransaction) -> Option<Verdict> {
+		self.decisions.iter()
+			.find(|decision| decision.is_between(&a.id, &b.id))
+			.map(|decision| decision.verdict)
+	}
+
+	// Every candidate shown to the user is remembered, the picked one as accepted and the rest as rejected.
+	// The new decisions are added at the end, and the earlier ones they replace are returned.
+	pub fn record(&mut self, target: &NormalTransaction, candidates: &[&NormalTransaction], picked: Option<&NormalTransaction>) -> Vec<Decision> {
+		let decided_at = Local::now();
+		let mut replaced = vec![];
+
+		for candidate in candidates {
+			let (from, to) = transfer_sides(target, candidate);
+			let verdict = match picked {
+				Some(picked) if picked.id == candidate.id => Verdict::Accepted,
+				_ => Verdict::Rejected,
+			};
+
+			let (earlier, kept) = std::mem::take(&mut self.decisions).into_iter()
+				.partition::<Vec<_>, _>(|decision| decision.is_between(&target.id, &candidate.id));
+			self.decisions = kept;
+			replaced.extend(earlier);
+			self.decisions.push(Decision {
+				transactions: (from.id.clone(), to.id.clone()),
+				accounts: (from.account.id.clone(), to.account.id.clone()),
+				descriptions: (from.additional_info.clone(), to.additional_info.clone()),
+				verdict,
+				description: format!("{from} <-> {to}"),
+				decided_at,
+			});
+		}
+
+		replaced
+	}
+
+	pub fn revoke(&mut self, transaction_id: &str) -> usize {
+		let before = self.decisions.len();
+		self.decisions.retain(|decision| !decision.involves(transaction_id));
+		before - self.decisions.len()
+	}
+
+	pub fn rule_for(&self, from: &NormalTransaction, to: &NormalTransaction) -> Option<&TransferRule> {
+		self.rules.iter().find(|rule| rule.applies(from, to))
+	}
+
+	// After enough identical accepted decisions between the same two accounts a rule is suggested,
+	// limited to a word every one of those transactions had in its description, if there is one.
+	pub fn suggest_rule(&self, config: &MatcherConfig, from: &NormalTransaction, to: &NormalTransaction) -> Option<TransferRule> {
+		if config.rule_suggestion_threshold == 0 { return None; }
+		if self.rules.iter().any(|rule| rule.is_for_accounts(from, to)) { return None; }
+
+		let accepted = self.decisions.iter()
+			.filter(|decision| decision.verdict == Verdict::Accepted)
+			.filter(|decision| decision.accounts == (from.account.id.clone(), to.account.id.clone()))
+			.collect::<Vec<_>>();
+		if accepted.len() < config.rule_suggestion_threshold { return None; }
+
+		let common_word = |description: &Option<String>, past_descriptions: Vec<&Option<String>>| {
+			let description = description.as_ref()?;
+			let past_words = past_descriptions.into_iter()
+				.map(|past_description| past_description.as_deref().map(|text| words(text).collect::<HashSet<_>>()).unwrap_or_default())
+				.collect::<Vec<_>>();
+
+			words(description).find(|word| past_words.iter().all(|past_words| past_words.contains(word)))
+		};
+
+		let description_contains = common_word(&from.additional_info, accepted.iter().map(|decision| &decision.descriptions.0).collect())
+			.or_else(|| common_word(&to.additional_info, accepted.iter().map(|decision| &decision.descriptions.1).collect()));
+
+		Some(TransferRule {
+			from_account: from.account.id.clone(),
+			to_account: to.account.id.clone(),
+			description_contains,
+			label: format!("{} -> {}", from.account, to.account),
+			enabled: true,
+		})
+	}
+}
@@ -0,0 +1,46 @@
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use crate::matcher::config::MatcherConfig;
+use crate::matcher::NormalTransaction;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExchangeRate {
+	pub from: String,
+	pub to: String,
+	pub rate: Decimal,
+}
+
+impl ExchangeRate {
+	pub fn rate(&self, from: &str, to: &str) -> Option<Decimal> {
+		if self.rate.is_zero() { return None; }
+
+		if self.from == from && self.to == to {
+			Some(self.rate)
+		} else if self.from == to && self.to == from {
+			Some(Decimal::ONE / self.rate)
+		} else {
+			None
+		}
+	}
+}
+
+// Rates reported by the bank on either transaction take precedence over the configured table.
+pub fn rate(config: &MatcherConfig, target: &NormalTransaction, candidate: &NormalTransaction) -> Option<Decimal> {
+	let (from, to) = (target.currency.as_str(), candidate.currency.as_str());
+
+	[&target.currency_exchange, &candidate.currency_exchange].into_iter()
+		.flatten()
+		.find_map(|currency_exchange| currency_exchange.rate(from, to))
+		.or_else(|| config.exchange_rates.iter().find_map(|exchange_rate| exchange_rate.rate(from, to)))
+}
+
+// Whether the target amount, converted to the candidate currency, cancels out the candidate amount within the tolerance.
+pub fn is_balanced(config: &MatcherConfig, target: &NormalTransaction, candidate: &NormalTransaction) -> bool {
+	let Some(rate) = rate(config, target, candidate) else { return false };
+	let Ok(tolerance) = Decimal::try_from(config.exchange_rate_tolerance) else { return false };
+
+	let converted = target.amount * rate;
+	let difference = (converted + candidate.amount).abs();
+
+	converted.is_sign_negative() != candidate.amount.is_sign_negative() && difference <= candidate.amount.abs() * tolerance
+}
@@ -0,0 +1,262 @@
+use std::fmt::{Display, Formatter};
+use std::rc::Rc;
+use color_eyre::eyre;
+use color_eyre::eyre::eyre;
+use rust_decimal::Decimal;
+use crate::matcher::assignment::{Assignment, MatchingStrategy};
+use crate::matcher::config::MatcherConfig;
+use crate::matcher::decisions::{Decisions, Verdict};
+use crate::matcher::score::Score;
+use crate::matcher::{exchange, fee, own_accounts, transfer_sides, MatchState, NormalTransaction, Transaction};
+use crate::nordigen::account::Account;
+use crate::nordigen::transaction::RawTransaction;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Outcome {
+	Accepted,
+	Review,
+	Rejected,
+}
+
+#[derive(Debug, Clone)]
+pub enum Reason {
+	DifferentAmount { difference: Decimal, tolerance: Decimal, currency: String },
+	ReceivedMore { difference: Decimal, currency: String },
+	NoExchangeRate { from: String, to: String },
+	DifferentExchangedAmount { converted: Decimal, currency: String, tolerance: f64 },
+	OutsideWindow { days: i64, window: i64 },
+	SameAccount,
+	SameDirection,
+	RejectedBefore,
+	AcceptedBefore,
+	Rule(String),
+	OtherRemembered,
+	OnlyAboveThreshold(Score, f64),
+	SeveralAboveThreshold(Score, f64, usize),
+	BelowOthers(Score, f64),
+	BelowThreshold(Score, f64),
+	Assigned(Score),
+	AssignedElsewhere(Score),
+	Ambiguous(Score),
+}
+
+impl Display for Reason {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Reason::DifferentAmount { difference, tolerance, currency } => write!(f, "amounts differ by {difference} {currency}, more than the fee tolerance of {tolerance} {currency}"),
+			Reason::ReceivedMore { difference, currency } => write!(f, "{difference} {currency} more is received than was sent"),
+			Reason::NoExchangeRate { from, to } => write!(f, "no exchange rate from {from} to {to}"),
+			Reason::DifferentExchangedAmount { converted, currency, tolerance } => write!(f, "converts to {converted} {currency}, off by more than the exchange rate tolerance of {}%", tolerance * 100.0),
+			Reason::OutsideWindow { days, window } => write!(f, "{days} days apart, outside the {window} day window"),
+			Reason::SameAccount => write!(f, "in the same account, so not a transfer between accounts"),
+			Reason::SameDirection => write!(f, "money goes the same way, so one side of a transfer cannot be the other"),
+			Reason::RejectedBefore => write!(f, "rejected in an earlier review"),
+			Reason::AcceptedBefore => write!(f, "accepted in an earlier review"),
+			Reason::Rule(label) => write!(f, "matches the transfer rule {label}"),
+			Reason::OtherRemembered => write!(f, "another candidate was accepted before or matches a transfer rule"),
+			Reason::OnlyAboveThreshold(score, threshold) => write!(f, "[{score}] the only candidate scoring at least {threshold:.2}"),
+			Reason::SeveralAboveThreshold(score, threshold, count) => write!(f, "[{score}] one of {count} candidates scoring at least {threshold:.2}"),
+			Reason::BelowOthers(score, threshold) => write!(f, "[{score}] not shown, as another candidate scores at least {threshold:.2}"),
+			Reason::BelowThreshold(score, threshold) => write!(f, "[{score}] below the auto accept score of {threshold:.2}"),
+			Reason::Assigned(score) => write!(f, "[{score}] part of the single best assignment of its cluster"),
+			Reason::AssignedElsewhere(score) => write!(f, "[{score}] assigned to another transaction in the best assignment of its cluster"),
+			Reason::Ambiguous(score) => write!(f, "[{score}] its cluster has several best assignments"),
+		}
+	}
+}
+
+#[derive(Debug)]
+pub struct Candidate {
+	pub transaction: NormalTransaction,
+	pub outcome: Outcome,
+	pub reason: Reason,
+}
+
+// How the matcher sees every other transaction as a partner for the target, on its own rather than after earlier transactions took theirs.
+#[derive(Debug)]
+pub struct Explanation {
+	pub target: NormalTransaction,
+	pub candidates: Vec<Candidate>,
+	pub same_side: usize,
+	pub unrelated: usize,
+	pub own_account: Option<String>,
+}
+
+impl Display for Explanation {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		writeln!(f, "{} ({})", self.target, self.target.id)?;
+		if self.target.previously_exported {
+			writeln!(f, "Exported in an earlier run, so it is only matched as a candidate of a new transaction")?;
+		}
+
+		for candidate in &self.candidates {
+			let outcome = match candidate.outcome {
+				Outcome::Accepted => "accepted",
+				Outcome::Review => "asked about",
+				Outcome::Rejected => "rejected",
+			};
+			writeln!(f, "  {outcome}: {} ({})", candidate.transaction, candidate.transaction.id)?;
+			writeln!(f, "    {}", candidate.reason)?;
+		}
+		if self.candidates.is_empty() {
+			writeln!(f, "  no candidates")?;
+		}
+
+		if self.same_side > 0 {
+			writeln!(f, "Not shown: {} transactions in the same account or going the same way, --all lists them", self.same_side)?;
+		}
+		if self.unrelated > 0 {
+			writeln!(f, "Not shown: {} transactions outside the date window with a different amount, --all lists them", self.unrelated)?;
+		}
+		if let Some(own_account) = &self.own_account {
+			writeln!(f, "Without a match, the counterparty makes it a transfer with own account {own_account}")?;
+		}
+
+		Ok(())
+	}
+}
+
+pub fn explain(raw_transactions: &[(RawTransaction, Rc<Account>)], previously_exported: Vec<NormalTransaction>, config: &MatcherConfig, decisions: &Decisions, transaction_id: &str, all: bool) -> eyre::Result<Explanation> {
+	let state = MatchState::new(raw_transactions.iter()
+		.map(Transaction::from)
+		.chain(previously_exported.into_iter().map(Transaction::Normal))
+		.collect());
+	let transactions = state.slots.iter()
+		.map(|slot| match slot {
+			Some(Transaction::Normal(transaction)) => transaction,
+			_ => unreachable!("nothing is matched yet"),
+		})
+		.collect::<Vec<_>>();
+
+	let target_position = transactions.iter()
+		.position(|transaction| transaction.id == transaction_id)
+		.ok_or_else(|| eyre!("There is no transaction {transaction_id} among the fetched and previously exported transactions"))?;
+	let target = transactions[target_position];
+
+	let assignment = match config.matching_strategy {
+		MatchingStrategy::Greedy => Assignment::default(),
+		MatchingStrategy::Optimal => Assignment::solve(config, &state.slots, &state.index, decisions),
+	};
+
+	let mut explanation = Explanation {
+		target: target.clone(),
+		candidates: vec![],
+		same_side: 0,
+		unrelated: 0,
+		own_account: None,
+	};
+	let mut scored = vec![];
+
+	for (position, candidate) in transactions.iter().enumerate() {
+		if position == target_position { continue; }
+		let same_account = candidate.account.id == target.account.id;
+		if same_account || candidate.amount.is_sign_negative() == target.amount.is_sign_negative() {
+			if all {
+				let reason = if same_account { Reason::SameAccount } else { Reason::SameDirection };
+				explanation.candidates.push(Candidate { transaction: (*candidate).clone(), outcome: Outcome::Rejected, reason });
+			} else {
+				explanation.same_side += 1;
+			}
+			continue;
+		}
+
+		let days = (target.date - candidate.date).num_days().abs();
+		if let Some(reason) = amount_difference(config, target, candidate) {
+			if days >= config.date_window_days && all {
+				// Being outside the window is enough to rule it out, whatever the amount.
+				explanation.candidates.push(Candidate {
+					transaction: (*candidate).clone(),
+					outcome: Outcome::Rejected,
+					reason: Reason::OutsideWindow { days, window: config.date_window_days },
+				});
+			} else if days >= config.date_window_days {
+				explanation.unrelated += 1;
+			} else {
+				explanation.candidates.push(Candidate { transaction: (*candidate).clone(), outcome: Outcome::Rejected, reason });
+			}
+			continue;
+		}
+
+		match Score::evaluate(config, target, candidate) {
+			Some(score) => scored.push((position, score)),
+			None => explanation.candidates.push(Candidate {
+				transaction: (*candidate).clone(),
+				outcome: Outcome::Rejected,
+				reason: Reason::OutsideWindow { days, window: config.date_window_days },
+			}),
+		}
+	}
+
+	let remembered = |position: usize| {
+		let candidate = transactions[position];
+		match decisions.verdict(target, candidate) {
+			Some(Verdict::Accepted) => Some(Reason::AcceptedBefore),
+			Some(Verdict::Rejected) => None,
+			None => {
+				let (from, to) = transfer_sides(target, candidate);
+				decisions.rule_for(from, to).map(|rule| Reason::Rule(rule.label.clone()))
+			},
+		}
+	};
+	let has_remembered = scored.iter().any(|(position, _)| remembered(*position).is_some());
+	let undecided = |position: usize| decisions.verdict(target, transactions[position]).is_none();
+	let above_threshold = scored.iter()
+		.filter(|(position, score)| undecided(*position) && assignment.is_available(*position, target_position) && score.total >= config.auto_accept_score)
+		.count();
+
+	for (position, score) in scored {
+		let threshold = config.auto_accept_score;
+		let (outcome, reason) = if let Some(reason) = remembered(position) {
+			(Outcome::Accepted, reason)
+		} else if !undecided(position) {
+			(Outcome::Rejected, Reason::RejectedBefore)
+		} else if has_remembered {
+			(Outcome::Rejected, Reason::OtherRemembered)
+		} else if assignment.partner(target_position) == Some(position) {
+			(Outcome::Accepted, Reason::Assigned(score))
+		} else if !assignment.is_available(position, target_position) {
+			(Outcome::Rejected, Reason::AssignedElsewhere(score))
+		} else if score.total < threshold && above_threshold > 0 {
+			(Outcome::Rejected, Reason::BelowOthers(score, threshold))
+		} else if score.total < threshold {
+			(Outcome::Review, Reason::BelowThreshold(score, threshold))
+		} else if assignment.is_ambiguous(target_position) {
+			(Outcome::Review, Reason::Ambiguous(score))
+		} else if above_threshold == 1 {
+			(Outcome::Accepted, Reason::OnlyAboveThreshold(score, threshold))
+		} else {
+			(Outcome::Review, Reason::SeveralAboveThreshold(score, threshold, above_threshold))
+		};
+
+		explanation.candidates.push(Candidate { transaction: transactions[position].clone(), outcome, reason });
+	}
+
+	explanation.candidates.sort_by_key(|candidate| (candidate.outcome, (candidate.transaction.date - target.date).num_days().abs()));
+	explanation.own_account = own_accounts::find(config, target).map(|own_account| own_account.name.clone());
+
+	Ok(explanation)
+}
+
+// Mirrors the amount checks of evaluate_match, none if the amounts would pass.
+fn amount_difference(config: &MatcherConfig, target: &NormalTransaction, candidate: &NormalTransaction) -> Option<Reason> {
+	if target.currency == candidate.currency {
+		if fee::is_within_tolerance(config, target, candidate) { return None; }
+
+		let currency = target.currency.clone();
+		return Some(match fee::fee(target, candidate) {
+			Some(difference) => Reason::DifferentAmount { difference, tolerance: fee::tolerance(config, target, candidate).unwrap_or_default(), currency },
+			None => Reason::ReceivedMore { difference: (target.amount + candidate.amount).abs(), currency },
+		});
+	}
+
+	if exchange::is_balanced(config, target, candidate) { return None; }
+
+	Some(match exchange::rate(config, target, candidate) {
+		Some(rate) => Reason::DifferentExchangedAmount {
+			converted: (target.amount * rate).abs().round_dp(2),
+			currency: candidate.currency.clone(),
+			tolerance: config.exchange_rate_tolerance,
+		},
+		None => Reason::NoExchangeRate { from: target.currency.clone(), to: candidate.currency.clone() },
+	})
+}
@@ -0,0 +1,32 @@
+use rust_decimal::Decimal;
+use crate::matcher::config::MatcherConfig;
+use crate::matcher::NormalTransaction;
+
+// The sending bank may deduct a fee, so the received amount can fall short of the sent amount by up to the tolerance.
+pub fn is_within_tolerance(config: &MatcherConfig, target: &NormalTransaction, candidate: &NormalTransaction) -> bool {
+	let Some(fee) = fee(target, candidate) else { return false };
+	let Some(tolerance) = tolerance(config, target, candidate) else { return false };
+
+	fee <= tolerance
+}
+
+pub fn tolerance(config: &MatcherConfig, target: &NormalTransaction, candidate: &NormalTransaction) -> Option<Decimal> {
+	let percentage = Decimal::try_from(config.fee_tolerance_percentage).ok()?;
+
+	let sent = target.amount.abs().max(candidate.amount.abs());
+	Some(config.fee_tolerance_amount.max(sent * percentage / Decimal::ONE_HUNDRED))
+}
+
+pub fn fee(target: &NormalTransaction, candidate: &NormalTransaction) -> Option<Decimal> {
+	if target.currency != candidate.currency { return None; }
+	if target.amount.is_sign_negative() == candidate.amount.is_sign_negative() { return None; }
+
+	let (sent, received) = if target.amount.is_sign_negative() {
+		(target.amount, candidate.amount)
+	} else {
+		(candidate.amount, target.amount)
+	};
+
+	let fee = -sent - received;
+	(fee >= Decimal::ZERO).then_some(fee)
+}
@@ -0,0 +1,95 @@
+use std::collections::{BTreeSet, HashMap};
+use chrono::{Duration, NaiveDate};
+use rust_decimal::Decimal;
+use crate::matcher::config::MatcherConfig;
+use crate::matcher::NormalTransaction;
+
+type Bucket = (String, bool);
+
+// Unmatched transactions by currency and direction sorted on absolute amount, and by currency sorted on date,
+// so candidates for a target can be looked up without going through every transaction.
+#[derive(Debug, Clone, Default)]
+pub struct CandidateIndex {
+	by_amount: HashMap<Bucket, BTreeSet<(Decimal, NaiveDate, usize)>>,
+	by_date: HashMap<String, BTreeSet<(NaiveDate, usize)>>,
+}
+
+impl CandidateIndex {
+	pub fn insert(&mut self, position: usize, transaction: &NormalTransaction) {
+		self.by_amount.entry((transaction.currency.clone(), transaction.amount.is_sign_negative()))
+			.or_default()
+			.insert((transaction.amount.abs(), transaction.date, position));
+		self.by_date.entry(transaction.currency.clone())
+			.or_default()
+			.insert((transaction.date, position));
+	}
+
+	pub fn remove(&mut self, position: usize, transaction: &NormalTransaction) {
+		if let Some(by_amount) = self.by_amount.get_mut(&(transaction.currency.clone(), transaction.amount.is_sign_negative())) {
+			by_amount.remove(&(transaction.amount.abs(), transaction.date, position));
+		}
+		if let Some(by_date) = self.by_date.get_mut(&transaction.currency) {
+			by_date.remove(&(transaction.date, position));
+		}
+	}
+
+	// Every position that could pass evaluate_match for the target, in ascending order. May include some that don't.
+	pub fn candidates(&self, config: &MatcherConfig, target: &NormalTransaction) -> Vec<usize> {
+		let Some((first_date, last_date)) = date_range(config, target) else { return vec![] };
+
+		let mut positions = vec![];
+
+		if let Some(by_amount) = self.by_amount.get(&(target.currency.clone(), !target.amount.is_sign_negative())) {
+			let amount = target.amount.abs();
+			let tolerance = amount_tolerance(config, amount);
+			let lowest = tolerance.map(|tolerance| amount - tolerance).unwrap_or(Decimal::MIN);
+			let highest = tolerance.map(|tolerance| amount + tolerance).unwrap_or(Decimal::MAX);
+
+			positions.extend(by_amount.range((lowest, first_date, 0)..=(highest, last_date, usize::MAX))
+				.filter(|(_, date, _)| (first_date..=last_date).contains(date))
+				.map(|(_, _, position)| *position));
+		}
+
+		for (currency, by_date) in self.by_date.iter() {
+			if *currency == target.currency { continue; }
+
+			positions.extend(by_date.range((first_date, 0)..=(last_date, usize::MAX))
+				.map(|(_, position)| *position));
+		}
+
+		positions.sort_unstable();
+		positions
+	}
+
+	// Positions in the same currency and date window going the other way, in ascending order.
+	pub fn opposite_in_window(&self, config: &MatcherConfig, target: &NormalTransaction) -> Vec<usize> {
+		let Some((first_date, last_date)) = date_range(config, target) else { return vec![] };
+		let Some(by_amount) = self.by_amount.get(&(target.currency.clone(), !target.amount.is_sign_negative())) else { return vec![] };
+
+		let mut positions = by_amount.range(..(target.amount.abs(), NaiveDate::MIN, 0))
+			.filter(|(_, date, _)| (first_date..=last_date).contains(date))
+			.map(|(_, _, position)| *position)
+			.collect::<Vec<_>>();
+
+		positions.sort_unstable();
+		positions
+	}
+}
+
+fn date_range(config: &MatcherConfig, target: &NormalTransaction) -> Option<(NaiveDate, NaiveDate)> {
+	if config.date_window_days <= 0 { return None; }
+
+	let reach = Duration::days(config.date_window_days - 1);
+	Some((target.date - reach, target.date + reach))
+}
+
+// How far a candidate amount can be from the target and still be within the fee tolerance, none if unbounded.
+fn amount_tolerance(config: &MatcherConfig, amount: Decimal) -> Option<Decimal> {
+	let percentage = Decimal::try_from(config.fee_tolerance_percentage).ok()?;
+	if percentage >= Decimal::ONE_HUNDRED { return None; }
+
+	let absolute = config.fee_tolerance_amount.max(Decimal::ZERO);
+	let relative = (amount + absolute) * percentage.max(Decimal::ZERO) / (Decimal::ONE_HUNDRED - percentage.max(Decimal::ZERO));
+
+	Some(absolute + relative)
+}
@@ -0,0 +1,609 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::rc::Rc;
+use chrono::NaiveDate;
+use color_eyre::eyre;
+use rust_decimal::Decimal;
+use rust_decimal::prelude::{Zero};
+use crate::matcher::assignment::{Assignment, MatchingStrategy};
+use crate::matcher::config::MatcherConfig;
+use crate::matcher::decisions::{Decision, Decisions, Verdict};
+use crate::matcher::index::CandidateIndex;
+use crate::matcher::score::Score;
+use crate::interactions;
+use crate::interactions::{Review, SummaryAction};
+use crate::nordigen::account::Account;
+use crate::nordigen::transaction::{Counterparty, CurrencyExchange, RawTransaction};
+
+pub mod assignment;
+pub mod config;
+pub mod decisions;
+pub mod exchange;
+pub mod explain;
+pub mod fee;
+pub mod index;
+pub mod own_accounts;
+pub mod rules;
+pub mod score;
+pub mod split;
+pub mod unmatched;
+
+#[derive(Debug, Clone)]
+pub enum Transaction {
+	Normal(NormalTransaction),
+	Transfer(TransferTransaction),
+	Split(SplitTransferTransaction),
+	Correction(NormalTransaction),
+}
+
+impl Display for Transaction {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Transaction::Normal(t) => write!(f, "{t}"),
+			Transaction::Transfer(t) => write!(f, "{t}"),
+			Transaction::Split(t) => write!(f, "{t}"),
+			Transaction::Correction(t) => write!(f, "reversal of: {t}"),
+		}
+	}
+}
+
+#[derive(Debug, Clone)]
+pub struct NormalTransaction {
+	pub id: String,
+	pub account: Rc<Account>,
+	pub amount: Decimal,
+	pub currency: String,
+	pub date: NaiveDate,
+	pub additional_info: Option<String>,
+	pub remittance_information: Option<String>,
+	pub currency_exchange: Option<CurrencyExchange>,
+	pub counterparty: Option<Counterparty>,
+	pub merchant_category_code: Option<String>,
+	pub category: Option<String>,
+	pub payee: Option<String>,
+	pub tags: Vec<String>,
+	pub memo: Option<String>,
+	pub balance: Option<Decimal>,
+	pub previously_exported: bool,
+}
+
+impl Display for NormalTransaction {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{} ", self.date)?;
+		if self.amount < Decimal::zero() {
+			write!(f, "from: {} ", self.account)?;
+			write!(f, "{} {} ", -self.amount, self.currency)?;
+		} else {
+			write!(f, "to: {} ", self.account)?;
+			write!(f, "{} {} ", self.amount, self.currency)?;
+		}
+		if let Some(additional_info) = &self.additional_info {
+			write!(f, "{additional_info}")?;
+		}
+
+		Ok(())
+	}
+}
+
+#[derive(Debug, Clone)]
+pub struct TransferTransaction {
+	pub from: Rc<Account>,
+	pub to: Rc<Account>,
+	pub amount: Decimal,
+	pub currency: String,
+	pub received_amount: Decimal,
+	pub received_currency: String,
+	pub fee: Option<Decimal>,
+	pub date: NaiveDate,
+	pub from_additional_info: Option<String>,
+	pub to_additional_info: Option<String>,
+	pub sources: Vec<NormalTransaction>,
+}
+
+impl TransferTransaction {
+	pub fn balance_after(&self, account: &Account) -> Option<Decimal> {
+		self.sources.iter()
+			.find(|source| source.account.id == account.id)
+			.and_then(|source| source.balance)
+	}
+}
+
+impl Display for TransferTransaction {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{} ", self.date)?;
+		if self.amount < Decimal::zero() {
+			write!(f, "from: {} ", self.from)?;
+			write!(f, "to: {} ", self.to)?;
+			write!(f, "{} {} ", -self.amount, self.currency)?;
+		} else {
+			write!(f, "from: {} ", self.to)?;
+			write!(f, "to: {} ", self.from)?;
+			write!(f, "{} {} ", self.amount, self.currency)?;
+		}
+		if self.received_currency != self.currency {
+			write!(f, "-> {} {} ", self.received_amount, self.received_currency)?;
+		}
+		if let Some(fee) = self.fee {
+			write!(f, "(fee {} {}) ", fee, self.currency)?;
+		}
+		if let Some(from_additional_info) = &self.from_additional_info {
+			write!(f, "{} ", from_additional_info)?;
+		}
+		if let Some(to_additional_info) = &self.to_additional_info {
+			write!(f, "{} ", to_additional_info)?;
+		}
+		Ok(())
+	}
+}
+
+// A single transaction on one side, settled by several legs on the other side.
+#[derive(Debug, Clone)]
+pub struct SplitTransferTransaction {
+	pub account: Rc<Account>,
+	pub amount: Decimal,
+	pub currency: String,
+	pub date: NaiveDate,
+	pub additional_info: Option<String>,
+	pub source: Box<NormalTransaction>,
+	pub legs: Vec<NormalTransaction>,
+}
+
+impl Display for SplitTransferTransaction {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{} ", self.date)?;
+		if self.amount < Decimal::zero() {
+			write!(f, "from: {} ", self.account)?;
+			write!(f, "{} {} ", -self.amount, self.currency)?;
+			write!(f, "to: ")?;
+		} else {
+			write!(f, "to: {} ", self.account)?;
+			write!(f, "{} {} ", self.amount, self.currency)?;
+			write!(f, "from: ")?;
+		}
+		for (index, leg) in self.legs.iter().enumerate() {
+			if index > 0 {
+				write!(f, ", ")?;
+			}
+			write!(f, "{} {} {}", leg.account, leg.amount.abs(), leg.currency)?;
+		}
+		if let Some(additional_info) = &self.additional_info {
+			write!(f, " {additional_info}")?;
+		}
+		Ok(())
+	}
+}
+
+// Every normal transaction, including the ones a transfer or split was made of.
+pub fn normal_transactions_mut(transactions: &mut [Transaction]) -> impl Iterator<Item = &mut NormalTransaction> {
+	transactions.iter_mut().flat_map(|transaction| -> Box<dyn Iterator<Item = &mut NormalTransaction>> {
+		match transaction {
+			Transaction::Normal(transaction) | Transaction::Correction(transaction) => Box::new(std::iter::once(transaction)),
+			Transaction::Transfer(transfer) => Box::new(transfer.sources.iter_mut()),
+			Transaction::Split(split) => Box::new(std::iter::once(split.source.as_mut()).chain(split.legs.iter_mut())),
+		}
+	})
+}
+
+impl From<&(RawTransaction, Rc<Account>)> for Transaction {
+	fn from((raw_transaction, account): &(RawTransaction, Rc<Account>)) -> Self {
+		Transaction::Normal(NormalTransaction {
+			id: raw_transaction.id.clone(),
+			account: account.clone(),
+			amount: raw_transaction.amount,
+			currency: raw_transaction.currency.clone(),
+			date: raw_transaction.date,
+			additional_info: raw_transaction.additional_info.clone(),
+			remittance_information: raw_transaction.remittance_information.clone(),
+			currency_exchange: raw_transaction.currency_exchange.clone(),
+			counterparty: raw_transaction.counterparty.clone(),
+			merchant_category_code: raw_transaction.merchant_category_code.clone(),
+			category: None,
+			payee: None,
+			tags: vec![],
+			memo: None,
+			balance: raw_transaction.balance,
+			previously_exported: false,
+		})
+	}
+}
+
+// Previously exported transactions are only considered as candidates, pairing one emits a reversal of it ahead of the transfer.
+// Without interaction, anything that would need a question is left unmatched.
+pub fn match_transactions(raw_transactions: &[(RawTransaction, Rc<Account>)], previously_exported: Vec<NormalTransaction>, config: &MatcherConfig, decisions: &mut Decisions, interactive: bool) -> eyre::Result<Vec<Transaction>> {
+	let mut state = MatchState::new(raw_transactions.iter()
+		.map(Transaction::from)
+		.chain(previously_exported.into_iter().map(Transaction::Normal))
+		.collect());
+	let assignment = match config.matching_strategy {
+		MatchingStrategy::Greedy => Assignment::default(),
+		MatchingStrategy::Optimal => Assignment::solve(config, &state.slots, &state.index, decisions),
+	};
+
+	let total = raw_transactions.len();
+	let mut position = 0;
+	let mut reviewed = vec![];
+	let mut history = History::default();
+
+	loop {
+		if position >= state.slots.len() {
+			if reviewed.is_empty() { break; }
+
+			let asked = match interactions::ReviewSummary::new(&reviewed).prompt()? {
+				SummaryAction::Confirm => break,
+				SummaryAction::Back => history.undo(&mut state, decisions),
+				SummaryAction::StartOver => history.undo_all(&mut state, decisions),
+			};
+			let Some((asked_position, answered)) = asked else { break };
+			position = asked_position;
+			reviewed.truncate(answered);
+			continue;
+		}
+
+		let step = {
+			let Some(Transaction::Normal(target)) = &state.slots[position] else { position += 1; continue };
+			if target.previously_exported { position += 1; continue; }
+			state.index.remove(position, target);
+			history.record(Change::Unindexed(position));
+
+			let mut scored_candidates = find_matches(config, &state.slots, &state.index, target);
+			scored_candidates.retain(|(_, candidate_position, _)| assignment.is_available(*candidate_position, position));
+
+			let remembered = scored_candidates.iter()
+				.find(|(candidate, _, _)| decisions.verdict(target, candidate) == Some(Verdict::Accepted))
+				.or_else(|| scored_candidates.iter()
+					.filter(|(candidate, _, _)| decisions.verdict(target, candidate).is_none())
+					.find(|(candidate, _, _)| {
+						let (from, to) = transfer_sides(target, candidate);
+						decisions.rule_for(from, to).is_some()
+					}))
+				.map(|(_, position, _)| *position)
+				.or_else(|| assignment.partner(position).filter(|partner| matches!(state.slots[*partner], Some(Transaction::Normal(_)))));
+			let undecided_candidates = scored_candidates.into_iter()
+				.filter(|(candidate, _, _)| decisions.verdict(target, candidate).is_none())
+				.collect::<Vec<_>>();
+
+			let step = match (remembered, pick_match(config, &undecided_candidates, assignment.is_ambiguous(position))) {
+				(Some(picked_position), _) | (None, Match::ObviousChoice(picked_position)) => Step::Pair(picked_position),
+				(None, Match::None) => Step::Nothing,
+				(None, Match::HumanInterventionRequired(_)) if !interactive => Step::Nothing,
+				(None, Match::HumanInterventionRequired(close_candidates)) => {
+					history.ask(position, &reviewed);
+
+					let progress = interactions::Progress { position: position + 1, total, answered: reviewed.len() };
+					let shown = close_candidates.iter().map(|(candidate, _, score)| (*candidate, *score)).collect::<Vec<_>>();
+					let review = interactions::MatchReview::new(progress, target, shown, history.can_go_back()).prompt()?;
+					let shown = close_candidates.iter().map(|(candidate, _, _)| *candidate).collect::<Vec<_>>();
+
+					match review {
+						Review::Back => Step::Back,
+						Review::Deferred => {
+							reviewed.push(format!("Deferred: {target}"));
+							Step::Nothing
+						},
+						Review::Rejected => {
+							let replaced = decisions.record(target, &shown, None);
+							history.record(Change::Decided { recorded: shown.len(), replaced });
+							reviewed.push(format!("Not a transfer: {target}"));
+							Step::Nothing
+						},
+						Review::Picked(picked) => {
+							let (candidate, picked_position, _) = close_candidates[picked];
+							let replaced = decisions.record(target, &shown, Some(candidate));
+							history.record(Change::Decided { recorded: shown.len(), replaced });
+
+							let (from, to) = transfer_sides(target, candidate);
+							reviewed.push(format!("Transfer: {from} <-> {to}"));
+							if let Some(rule) = decisions.suggest_rule(config, from, to) {
+								let rule = interactions::SaveRuleConfirm::new(rule).prompt()?;
+								decisions.rules.push(rule);
+								history.record(Change::RuleSaved);
+							}
+
+							Step::Pair(picked_position)
+						},
+					}
+				},
+			};
+
+			match step {
+				Step::Nothing => if let Some(own_account) = own_accounts::find(config, target) {
+					Step::Transfer(own_accounts::transfer(target, own_account))
+				} else if config.split_transfers && interactive {
+					let candidates = state.index.opposite_in_window(config, target).into_iter()
+						.filter_map(|position| match &state.slots[position] {
+							Some(Transaction::Normal(candidate)) => Some((position, candidate)),
+							_ => None,
+						});
+					let splits = split::find_splits(config, candidates, target);
+					if splits.is_empty() {
+						Step::Nothing
+					} else {
+						history.ask(position, &reviewed);
+
+						let progress = interactions::Progress { position: position + 1, total, answered: reviewed.len() };
+						match interactions::SplitReview::new(progress, target, &splits, history.can_go_back()).prompt()? {
+							Review::Back => Step::Back,
+							Review::Rejected | Review::Deferred => {
+								reviewed.push(format!("Not split: {target}"));
+								Step::Nothing
+							},
+							Review::Picked(picked) => {
+								reviewed.push(format!("Split: {target} <-> {} transactions", splits[picked].legs.len()));
+								Step::Split(splits[picked].legs.iter().map(|leg| leg.position).collect())
+							},
+						}
+					}
+				} else {
+					Step::Nothing
+				},
+				step => step,
+			}
+		};
+
+		match step {
+			Step::Back => {
+				let current = history.undo(&mut state, decisions);
+				let Some((asked_position, answered)) = history.undo(&mut state, decisions).or(current) else { continue };
+				position = asked_position;
+				reviewed.truncate(answered);
+				continue;
+			},
+			Step::Pair(picked_position) => {
+				let picked = state.take(picked_position, &mut history);
+				let target = state.take(position, &mut history);
+
+				if picked.previously_exported {
+					state.corrections.insert(position, Transaction::Correction(picked.clone()));
+					history.record(Change::Corrected(position));
+				}
+				state.place(position, Transaction::Transfer(pair(target, picked)), &mut history);
+			},
+			Step::Transfer(transfer) => {
+				state.take(position, &mut history);
+				state.place(position, Transaction::Transfer(transfer), &mut history);
+			},
+			Step::Split(leg_positions) => {
+				let mut legs = leg_positions.into_iter()
+					.map(|leg_position| state.take(leg_position, &mut history))
+					.collect::<Vec<_>>();
+				legs.sort_by_key(|leg| leg.date);
+				let target = state.take(position, &mut history);
+
+				state.place(position, Transaction::Split(split_transfer(target, legs)), &mut history);
+			},
+			Step::Nothing => {},
+		}
+
+		position += 1;
+	}
+
+	Ok(state.into_transactions())
+}
+
+enum Step {
+	Pair(usize),
+	Transfer(TransferTransaction),
+	Split(Vec<usize>),
+	Back,
+	Nothing,
+}
+
+// Transactions keep their position while matching, matched ones leave an empty slot behind instead of shifting everything after them.
+struct MatchState {
+	slots: Vec<Option<Transaction>>,
+	corrections: HashMap<usize, Transaction>,
+	index: CandidateIndex,
+}
+
+impl MatchState {
+	fn new(transactions: Vec<Transaction>) -> MatchState {
+		let mut index = CandidateIndex::default();
+		for (position, transaction) in transactions.iter().enumerate() {
+			if let Transaction::Normal(transaction) = transaction {
+				index.insert(position, transaction);
+			}
+		}
+
+		MatchState {
+			slots: transactions.into_iter().map(Some).collect(),
+			corrections: HashMap::new(),
+			index,
+		}
+	}
+
+	fn take(&mut self, position: usize, history: &mut History) -> NormalTransaction {
+		let Some(Transaction::Normal(transaction)) = self.slots[position].take() else { unreachable!("only normal transactions are matched") };
+		self.index.remove(position, &transaction);
+		history.record(Change::Taken(position, Box::new(transaction.clone())));
+		transaction
+	}
+
+	fn place(&mut self, position: usize, transaction: Transaction, history: &mut History) {
+		self.slots[position] = Some(transaction);
+		history.record(Change::Placed(position));
+	}
+
+	fn into_transactions(mut self) -> Vec<Transaction> {
+		let mut transactions = vec![];
+		for (position, slot) in self.slots.into_iter().enumerate() {
+			if let Some(correction) = self.corrections.remove(&position) {
+				transactions.push(correction);
+			}
+			transactions.extend(slot);
+		}
+		transactions
+	}
+}
+
+// Every change made since the first question, so going back to a question reverts the changes made since it one by one.
+// Nothing before the first question can be gone back to, so those changes aren't kept.
+#[derive(Default)]
+struct History {
+	changes: Vec<Change>,
+	questions: usize,
+}
+
+enum Change {
+	Asked { position: usize, answered: usize },
+	Unindexed(usize),
+	Taken(usize, Box<NormalTransaction>),
+	Placed(usize),
+	Corrected(usize),
+	Decided { recorded: usize, replaced: Vec<Decision> },
+	RuleSaved,
+}
+
+impl History {
+	fn ask(&mut self, position: usize, reviewed: &[String]) {
+		self.changes.push(Change::Asked { position, answered: reviewed.len() });
+		self.questions += 1;
+	}
+
+	fn can_go_back(&self) -> bool {
+		self.questions > 1
+	}
+
+	fn record(&mut self, change: Change) {
+		if self.questions > 0 {
+			self.changes.push(change);
+		}
+	}
+
+	// Back to before the last question, giving the position it was asked at and the number of answers before it.
+	fn undo(&mut self, state: &mut MatchState, decisions: &mut Decisions) -> Option<(usize, usize)> {
+		while let Some(change) = self.changes.pop() {
+			match change {
+				Change::Asked { position, answered } => {
+					self.questions -= 1;
+					return Some((position, answered));
+				},
+				Change::Unindexed(position) => if let Some(Transaction::Normal(transaction)) = &state.slots[position] {
+					state.index.insert(position, transaction);
+				},
+				Change::Taken(position, transaction) => {
+					state.index.insert(position, &transaction);
+					state.slots[position] = Some(Transaction::Normal(*transaction));
+				},
+				Change::Placed(position) => state.slots[position] = None,
+				Change::Corrected(position) => {
+					state.corrections.remove(&position);
+				},
+				Change::Decided { recorded, replaced } => {
+					decisions.decisions.truncate(decisions.decisions.len() - recorded);
+					decisions.decisions.extend(replaced);
+				},
+				Change::RuleSaved => {
+					decisions.rules.pop();
+				},
+			}
+		}
+		None
+	}
+
+	// Back to before the first question.
+	fn undo_all(&mut self, state: &mut MatchState, decisions: &mut Decisions) -> Option<(usize, usize)> {
+		let mut first = None;
+		while let Some(asked) = self.undo(state, decisions) {
+			first = Some(asked);
+		}
+		first
+	}
+}
+
+pub fn pair(a: NormalTransaction, b: NormalTransaction) -> TransferTransaction {
+	let (from, to) = if a.amount < Decimal::zero() { (a, b) } else { (b, a) };
+
+	TransferTransaction {
+		from: from.account.clone(),
+		to: to.account.clone(),
+		amount: from.amount.abs(),
+		currency: from.currency.clone(),
+		received_amount: to.amount.abs(),
+		received_currency: to.currency.clone(),
+		fee: fee::fee(&from, &to).filter(|fee| !fee.is_zero()),
+		date: from.date,
+		from_additional_info: from.additional_info.clone(),
+		to_additional_info: to.additional_info.clone(),
+		sources: vec![from, to],
+	}
+}
+
+pub fn transfer_sides<'a>(target: &'a NormalTransaction, candidate: &'a NormalTransaction) -> (&'a NormalTransaction, &'a NormalTransaction) {
+	if target.amount < Decimal::zero() {
+		(target, candidate)
+	} else {
+		(candidate, target)
+	}
+}
+
+fn split_transfer(target: NormalTransaction, legs: Vec<NormalTransaction>) -> SplitTransferTransaction {
+	let date = if target.amount < Decimal::zero() {
+		target.date
+	} else {
+		legs.iter().map(|leg| leg.date).min().unwrap_or(target.date)
+	};
+
+	SplitTransferTransaction {
+		account: target.account.clone(),
+		amount: target.amount,
+		currency: target.currency.clone(),
+		date,
+		additional_info: target.additional_info.clone(),
+		source: Box::new(target),
+		legs,
+	}
+}
+
+enum Match<'a> {
+	HumanInterventionRequired(Vec<(&'a NormalTransaction, usize, Score)>),
+	ObviousChoice(usize),
+	None,
+}
+
+// In an ambiguous cluster even a single acceptable candidate is asked about, as it may belong to another transaction.
+fn pick_match<'a>(config: &MatcherConfig, scored_candidates: &[(&'a NormalTransaction, usize, Score)], always_ask: bool) -> Match<'a> {
+	let accepted_candidates = scored_candidates.iter()
+		.copied()
+		.filter(|(_, _ , score)| score.total >= config.auto_accept_score)
+		.collect::<Vec<_>>();
+
+	if let Some((_, index, _)) = accepted_candidates.first() {
+		return if accepted_candidates.len() == 1 && !always_ask {
+			Match::ObviousChoice(*index)
+		} else {
+			Match::HumanInterventionRequired(accepted_candidates)
+		}
+	}
+
+	if scored_candidates.is_empty() {
+		Match::None
+	} else {
+		Match::HumanInterventionRequired(scored_candidates.to_vec())
+	}
+}
+
+fn find_matches<'a>(config: &MatcherConfig, slots: &'a [Option<Transaction>], index: &CandidateIndex, target: &NormalTransaction) -> Vec<(&'a NormalTransaction, usize, Score)> {
+	let mut res = vec![];
+
+	for position in index.candidates(config, target) {
+		let Some(Transaction::Normal(candidate)) = &slots[position] else { continue };
+		let Some(score) = evaluate_match(config, target, candidate) else { continue };
+		res.push((candidate, position, score));
+	}
+
+	res.sort_unstable_by_key(|scored_candidate| Reverse(scored_candidate.2));
+
+	res
+}
+
+pub fn evaluate_match(config: &MatcherConfig, target: &NormalTransaction, candidate: &NormalTransaction) -> Option<Score> {
+	if target.account.id == candidate.account.id { return None };
+	if target.currency == candidate.currency {
+		if !fee::is_within_tolerance(config, target, candidate) { return None; }
+	} else if !exchange::is_balanced(config, target, candidate) {
+		return None;
+	}
+
+	Score::evaluate(config, target, candidate)
+}
@@ -0,0 +1,81 @@
+use std::rc::Rc;
+use serde::{Deserialize, Serialize};
+use crate::matcher::config::MatcherConfig;
+use crate::matcher::{NormalTransaction, TransferTransaction};
+use crate::nordigen::account::Account;
+
+// An account of our own at a bank njord doesn't fetch from, recognised by the counterparty of a transaction.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OwnAccount {
+	pub name: String,
+	#[serde(default)]
+	pub iban: Option<String>,
+	#[serde(default)]
+	pub bban: Option<String>,
+	#[serde(default)]
+	pub counterparty_name: Option<String>,
+}
+
+impl OwnAccount {
+	pub fn matches(&self, transaction: &NormalTransaction) -> bool {
+		let Some(counterparty) = &transaction.counterparty else { return false };
+
+		let same_number = |own: &Option<String>, theirs: &Option<String>| matches!(
+			(own, theirs),
+			(Some(own), Some(theirs)) if normalize(own) == normalize(theirs)
+		);
+		let same_name = matches!(
+			(&self.counterparty_name, &counterparty.name),
+			(Some(own), Some(theirs)) if own.trim().eq_ignore_ascii_case(theirs.trim())
+		);
+
+		same_number(&self.iban, &counterparty.iban) || same_number(&self.bban, &counterparty.bban) || same_name
+	}
+
+	fn account(&self) -> Account {
+		Account {
+			id: format!("external:{}", self.name),
+			institution_id: String::new(),
+			bban: self.bban.clone(),
+			iban: self.iban.clone(),
+			status: "external".to_string(),
+			name: Some(self.name.clone()),
+			display_name: None,
+			owner_name: None,
+		}
+	}
+}
+
+pub fn find<'a>(config: &'a MatcherConfig, transaction: &NormalTransaction) -> Option<&'a OwnAccount> {
+	config.own_accounts.iter().find(|own_account| own_account.matches(transaction))
+}
+
+pub fn transfer(transaction: &NormalTransaction, own_account: &OwnAccount) -> TransferTransaction {
+	let external = Rc::new(own_account.account());
+	let (from, to) = if transaction.amount.is_sign_negative() {
+		(transaction.account.clone(), external)
+	} else {
+		(external, transaction.account.clone())
+	};
+
+	TransferTransaction {
+		from,
+		to,
+		amount: transaction.amount.abs(),
+		currency: transaction.currency.clone(),
+		received_amount: transaction.amount.abs(),
+		received_currency: transaction.currency.clone(),
+		fee: None,
+		date: transaction.date,
+		from_additional_info: transaction.additional_info.clone().filter(|_| transaction.amount.is_sign_negative()),
+		to_additional_info: transaction.additional_info.clone().filter(|_| !transaction.amount.is_sign_negative()),
+		sources: vec![transaction.clone()],
+	}
+}
+
+fn normalize(account_number: &str) -> String {
+	account_number.chars()
+		.filter(|c| !c.is_whitespace())
+		.collect::<String>()
+		.to_uppercase()
+}
@@ -0,0 +1,42 @@
+use std::fmt::{Display, Formatter};
+use serde::{Deserialize, Serialize};
+use crate::matcher::NormalTransaction;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransferRule {
+	pub from_account: String,
+	pub to_account: String,
+	pub description_contains: Option<String>,
+	pub label: String,
+	pub enabled: bool,
+}
+
+impl TransferRule {
+	pub fn is_for_accounts(&self, from: &NormalTransaction, to: &NormalTransaction) -> bool {
+		self.from_account == from.account.id && self.to_account == to.account.id
+	}
+
+	pub fn applies(&self, from: &NormalTransaction, to: &NormalTransaction) -> bool {
+		if !self.enabled || !self.is_for_accounts(from, to) { return false; }
+
+		let Some(description_contains) = &self.description_contains else { return true };
+		let description_contains = description_contains.to_lowercase();
+
+		[&from.additional_info, &to.additional_info].into_iter()
+			.flatten()
+			.any(|additional_info| additional_info.to_lowercase().contains(&description_contains))
+	}
+}
+
+impl Display for TransferRule {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}", self.label)?;
+		if let Some(description_contains) = &self.description_contains {
+			write!(f, " with description containing \"{description_contains}\"")?;
+		}
+		if !self.enabled {
+			write!(f, " (declined)")?;
+		}
+		Ok(())
+	}
+}
@@ -0,0 +1,80 @@
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::fmt::{Display, Formatter};
+use chrono::Duration;
+use crate::matcher::config::MatcherConfig;
+use crate::matcher::NormalTransaction;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Score {
+	pub total: f64,
+	pub date_difference: Duration,
+}
+
+impl Score {
+	// A candidate on the same date scores 1, dropping linearly to 0 at the edge of the date window.
+	// Shared institution, owner and description similarity are added on top, weighted by the config.
+	pub fn evaluate(config: &MatcherConfig, target: &NormalTransaction, candidate: &NormalTransaction) -> Option<Score> {
+		let date_difference = target.date - candidate.date;
+		let days_apart = date_difference.num_days().abs();
+		if days_apart >= config.date_window_days { return None; }
+
+		let mut total = 1.0 - days_apart as f64 / config.date_window_days as f64;
+
+		if target.account.institution_id == candidate.account.institution_id {
+			total += config.same_institution_weight;
+		}
+
+		let is_same_owner = matches!(
+			(&target.account.owner_name, &candidate.account.owner_name),
+			(Some(target_owner), Some(candidate_owner)) if target_owner.eq_ignore_ascii_case(candidate_owner)
+		);
+		if is_same_owner {
+			total += config.same_owner_weight;
+		}
+
+		if config.description_weight != 0.0 {
+			total += config.description_weight * description_similarity(&target.additional_info, &candidate.additional_info);
+		}
+
+		Some(Score { total, date_difference })
+	}
+}
+
+impl Eq for Score {}
+
+impl PartialOrd for Score {
+	fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+		Some(self.cmp(other))
+	}
+}
+
+impl Ord for Score {
+	fn cmp(&self, other: &Self) -> Ordering {
+		self.total.total_cmp(&other.total)
+			.then_with(|| other.date_difference.num_days().abs().cmp(&self.date_difference.num_days().abs()))
+	}
+}
+
+impl Display for Score {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{:.2}, {} days apart", self.total, self.date_difference.num_days().abs())
+	}
+}
+
+fn description_similarity(a: &Option<String>, b: &Option<String>) -> f64 {
+	let (Some(a), Some(b)) = (a, b) else { return 0.0 };
+
+	let (a, b) = (words(a).collect::<HashSet<_>>(), words(b).collect::<HashSet<_>>());
+
+	let union = a.union(&b).count();
+	if union == 0 { return 0.0; }
+
+	a.intersection(&b).count() as f64 / union as f64
+}
+
+pub fn words(text: &str) -> impl Iterator<Item = String> + '_ {
+	text.split(|c: char| !c.is_alphanumeric())
+		.filter(|word| !word.is_empty())
+		.map(str::to_lowercase)
+}
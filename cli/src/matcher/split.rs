@@ -0,0 +1,84 @@
+use std::cmp::Reverse;
+use std::fmt::{Display, Formatter};
+use rust_decimal::Decimal;
+use crate::matcher::config::MatcherConfig;
+use crate::matcher::score::Score;
+use crate::matcher::NormalTransaction;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Leg<'a> {
+	pub transaction: &'a NormalTransaction,
+	pub position: usize,
+	pub score: Score,
+}
+
+#[derive(Debug)]
+pub struct Split<'a> {
+	pub legs: Vec<Leg<'a>>,
+	pub score: Score,
+}
+
+impl<'a> Split<'a> {
+	fn new(legs: Vec<Leg<'a>>) -> Split<'a> {
+		let score = legs.iter()
+			.map(|leg| leg.score)
+			.min()
+			.expect("a split has at least two legs");
+
+		Split { legs, score }
+	}
+}
+
+impl<'a> Display for Split<'a> {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		write!(f, "[{}] ", self.score)?;
+		for (index, leg) in self.legs.iter().enumerate() {
+			if index > 0 {
+				write!(f, " + ")?;
+			}
+			write!(f, "{}", leg.transaction)?;
+		}
+		Ok(())
+	}
+}
+
+// Combinations of same currency candidates on the other side of the target whose amounts add up to exactly the target amount.
+pub fn find_splits<'a>(config: &MatcherConfig, candidates: impl Iterator<Item = (usize, &'a NormalTransaction)>, target: &NormalTransaction) -> Vec<Split<'a>> {
+	let eligible = candidates
+		.filter_map(|(position, candidate)| {
+			if candidate.previously_exported { return None; }
+			if candidate.account.id == target.account.id || candidate.currency != target.currency { return None; }
+			if candidate.amount.is_zero() || candidate.amount.is_sign_negative() == target.amount.is_sign_negative() { return None; }
+			if candidate.amount.abs() >= target.amount.abs() { return None; }
+
+			let score = Score::evaluate(config, target, candidate)?;
+			Some(Leg { transaction: candidate, position, score })
+		})
+		.collect::<Vec<_>>();
+
+	let mut splits = vec![];
+	collect_splits(config, &eligible, target.amount.abs(), 0, &mut vec![], &mut splits);
+
+	splits.sort_unstable_by_key(|split| (split.legs.len(), Reverse(split.score)));
+
+	splits
+}
+
+fn collect_splits<'a>(config: &MatcherConfig, eligible: &[Leg<'a>], remaining: Decimal, start: usize, picked: &mut Vec<Leg<'a>>, splits: &mut Vec<Split<'a>>) {
+	if remaining.is_zero() {
+		if picked.len() >= 2 {
+			splits.push(Split::new(picked.clone()));
+		}
+		return;
+	}
+	if picked.len() >= config.max_split_legs { return; }
+
+	for (offset, leg) in eligible[start..].iter().enumerate() {
+		let amount = leg.transaction.amount.abs();
+		if amount > remaining { continue; }
+
+		picked.push(*leg);
+		collect_splits(config, eligible, remaining - amount, start + offset + 1, picked, splits);
+		picked.pop();
+	}
+}
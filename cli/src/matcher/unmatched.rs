@@ -0,0 +1,104 @@
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+use chrono::{Duration, Local, NaiveDate};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use crate::matcher::{NormalTransaction, Transaction};
+use crate::nordigen::account::Account;
+use crate::nordigen::transaction::{Counterparty, CurrencyExchange, RawTransaction};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredTransaction {
+	pub id: String,
+	pub account: Account,
+	pub amount: Decimal,
+	pub currency: String,
+	pub date: NaiveDate,
+	pub additional_info: Option<String>,
+	#[serde(default)]
+	pub remittance_information: Option<String>,
+	pub currency_exchange: Option<CurrencyExchange>,
+	#[serde(default)]
+	pub counterparty: Option<Counterparty>,
+	#[serde(default)]
+	pub merchant_category_code: Option<String>,
+	#[serde(default)]
+	pub category: Option<String>,
+	#[serde(default)]
+	pub payee: Option<String>,
+	#[serde(default)]
+	pub tags: Vec<String>,
+	#[serde(default)]
+	pub memo: Option<String>,
+	#[serde(default)]
+	pub balance: Option<Decimal>,
+}
+
+// Transactions that were exported without being matched, so a transfer whose other half arrives in a later run can still be paired.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct UnmatchedStore {
+	pub transactions: Vec<StoredTransaction>,
+}
+
+impl UnmatchedStore {
+	pub fn into_transactions(self, raw_transactions: &[(RawTransaction, Rc<Account>)]) -> Vec<NormalTransaction> {
+		let fetched_ids = raw_transactions.iter()
+			.map(|(raw_transaction, _)| raw_transaction.id.as_str())
+			.collect::<HashSet<_>>();
+
+		let mut accounts: HashMap<String, Rc<Account>> = HashMap::new();
+
+		self.transactions.into_iter()
+			.filter(|stored| !fetched_ids.contains(stored.id.as_str()))
+			.map(|stored| NormalTransaction {
+				account: accounts.entry(stored.account.id.clone())
+					.or_insert_with(|| Rc::new(stored.account))
+					.clone(),
+				id: stored.id,
+				amount: stored.amount,
+				currency: stored.currency,
+				date: stored.date,
+				additional_info: stored.additional_info,
+				remittance_information: stored.remittance_information,
+				currency_exchange: stored.currency_exchange,
+				counterparty: stored.counterparty,
+				merchant_category_code: stored.merchant_category_code,
+				category: stored.category,
+				payee: stored.payee,
+				tags: stored.tags,
+				memo: stored.memo,
+				balance: stored.balance,
+				previously_exported: true,
+			})
+			.collect()
+	}
+
+	pub fn from_transactions(transactions: &[Transaction], retention_days: i64) -> UnmatchedStore {
+		let oldest = Local::now().date_naive() - Duration::days(retention_days);
+
+		let transactions = transactions.iter()
+			.filter_map(|transaction| match transaction {
+				Transaction::Normal(transaction) if transaction.date >= oldest => Some(StoredTransaction {
+					id: transaction.id.clone(),
+					account: transaction.account.as_ref().clone(),
+					amount: transaction.amount,
+					currency: transaction.currency.clone(),
+					date: transaction.date,
+					additional_info: transaction.additional_info.clone(),
+					remittance_information: transaction.remittance_information.clone(),
+					currency_exchange: transaction.currency_exchange.clone(),
+					counterparty: transaction.counterparty.clone(),
+					merchant_category_code: transaction.merchant_category_code.clone(),
+					category: transaction.category.clone(),
+					payee: transaction.payee.clone(),
+					tags: transaction.tags.clone(),
+					memo: transaction.memo.clone(),
+					balance: transaction.balance,
+				}),
+				_ => None,
+			})
+			.collect();
+
+		UnmatchedStore { transactions }
+	}
+}
@@ -1,34 +1,38 @@
 use std::fmt::{Display, Formatter};
-use color_eyre::eyre;
 use serde::{Deserialize, Serialize};
-use crate::HTTP_CLIENT;
 use crate::nordigen::client_credentials::ClientCredentials;
+use crate::nordigen::api_error::ApiError;
 use crate::nordigen::http_interface;
+use crate::nordigen::http_interface::ApiClient;
 use crate::nordigen::token::Token;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Account {
 	pub id: String,
+	pub institution_id: String,
 	pub bban: Option<String>,
 	pub iban: Option<String>,
 	pub status: String,
 	pub name: Option<String>,
-	pub display_name: Option<String>
+	pub display_name: Option<String>,
+	pub owner_name: Option<String>,
 }
 
 impl Account {
-	pub fn get(client_credentials: &ClientCredentials, token: &mut Token, id: &str) -> eyre::Result<Account> {
-		let access_token = token.get_access_token(client_credentials)?;
+	pub fn get(client: &ApiClient, client_credentials: &ClientCredentials, token: &mut Token, institution_id: &str, id: &str) -> Result<Account, ApiError> {
+		let access_token = token.get_access_token(client, client_credentials)?;
 
-		let res = http_interface::accounts::details::get(&HTTP_CLIENT, access_token, id)?;
+		let res = http_interface::accounts::details::get(client, access_token, id)?;
 
 		Ok(Account {
 			id: id.to_string(),
+			institution_id: institution_id.to_string(),
 			bban: res.account.bban,
 			iban: res.account.iban,
 			status: res.account.status,
 			name: res.account.name,
 			display_name: res.account.display_name,
+			owner_name: res.account.owner_name,
 		})
 	}
 
@@ -0,0 +1,120 @@
+use std::fmt::{Display, Formatter};
+use chrono::{Duration, Local};
+use reqwest::StatusCode;
+use serde::Deserialize;
+use crate::nordigen::http_interface::ApiResponse;
+use crate::nordigen::rate_limit::RateLimit;
+
+#[derive(Debug)]
+pub enum ApiError {
+	Unauthorized { detail: String },
+	RateLimited { retry_after: Option<Duration>, detail: String },
+	NotFound { detail: String },
+	AccessExpired { detail: String },
+	InstitutionDown { detail: String },
+	Validation { field: Option<String>, detail: String },
+	Unexpected { status: StatusCode, summary: String, detail: String },
+	Transport(reqwest::Error),
+	Decode(serde_json::Error),
+	InvalidUrl(String),
+	NotRecorded { method: String, endpoint: String },
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct ErrorBody {
+	summary: Option<String>,
+	detail: Option<String>,
+	status_code: Option<u16>,
+}
+
+impl ApiError {
+	// The status code in the body is the one Nordigen meant, a proxy in between may have answered with another.
+	pub fn from_response(res: ApiResponse) -> ApiError {
+		let retry_after = RateLimit::from_headers(&res.headers, Local::now())
+			.map(|rate_limit| rate_limit.time_til_reset());
+
+		let body = String::from_utf8_lossy(&res.body);
+
+		let (field, error_body) = ApiError::parse_body(&body);
+		let status = error_body.status_code
+			.and_then(|status_code| StatusCode::from_u16(status_code).ok())
+			.unwrap_or(res.status);
+		let summary = error_body.summary.unwrap_or_else(|| status.to_string());
+		let detail = error_body.detail.unwrap_or_else(|| summary.clone());
+
+		match status {
+			StatusCode::BAD_REQUEST => ApiError::Validation { field, detail },
+			StatusCode::UNAUTHORIZED => ApiError::Unauthorized { detail },
+			StatusCode::FORBIDDEN => ApiError::AccessExpired { detail },
+			StatusCode::NOT_FOUND => ApiError::NotFound { detail },
+			StatusCode::TOO_MANY_REQUESTS => ApiError::RateLimited { retry_after, detail },
+			StatusCode::BAD_GATEWAY | StatusCode::SERVICE_UNAVAILABLE | StatusCode::GATEWAY_TIMEOUT => ApiError::InstitutionDown { detail },
+			status => ApiError::Unexpected { status, summary, detail },
+		}
+	}
+
+	// Nordigen either reports errors at the top level, or per offending field of the request body with the status code next to the fields.
+	fn parse_body(body: &str) -> (Option<String>, ErrorBody) {
+		let Ok(json) = serde_json::from_str::<serde_json::Value>(body) else {
+			return (None, ErrorBody { detail: Some(body.to_string()).filter(|body| !body.is_empty()), ..ErrorBody::default() });
+		};
+
+		if let Ok(error_body @ ErrorBody { summary: Some(_), .. }) = serde_json::from_value::<ErrorBody>(json.clone()) {
+			return (None, error_body);
+		}
+
+		let field_error = json.as_object()
+			.into_iter()
+			.flatten()
+			.find_map(|(field, value)| {
+				let error_body = serde_json::from_value::<ErrorBody>(value.clone()).ok()?;
+				error_body.summary.is_some().then(|| (Some(field.clone()), error_body))
+			});
+
+		let (field, error_body) = field_error.unwrap_or_default();
+		let status_code = json.get("status_code").and_then(|status_code| status_code.as_u64()).and_then(|status_code| u16::try_from(status_code).ok());
+		(field, ErrorBody { status_code, ..error_body })
+	}
+}
+
+impl Display for ApiError {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		match self {
+			ApiError::Unauthorized { detail } => write!(f, "Nordigen rejected the credentials, check the client ID and secret ({detail})"),
+			ApiError::RateLimited { retry_after: Some(retry_after), detail } => write!(f, "Nordigen rate limit reached, try again in {} minutes ({detail})", retry_after.num_minutes() + 1),
+			ApiError::RateLimited { retry_after: None, detail } => write!(f, "Nordigen rate limit reached, try again later ({detail})"),
+			ApiError::NotFound { detail } => write!(f, "Not found ({detail})"),
+			ApiError::AccessExpired { detail } => write!(f, "Access has expired or been revoked, the institution needs to be linked again ({detail})"),
+			ApiError::InstitutionDown { detail } => write!(f, "The institution is currently unavailable, try again later ({detail})"),
+			ApiError::Validation { field: Some(field), detail } => write!(f, "Nordigen rejected the request, invalid {field} ({detail})"),
+			ApiError::Validation { field: None, detail } => write!(f, "Nordigen rejected the request ({detail})"),
+			ApiError::Unexpected { status, summary, detail } => write!(f, "[{status}] {summary} ({detail})"),
+			ApiError::Transport(err) => write!(f, "Unable to reach Nordigen ({err})"),
+			ApiError::Decode(err) => write!(f, "Unable to understand response from Nordigen ({err})"),
+			ApiError::InvalidUrl(url) => write!(f, "Invalid URL {url}"),
+			ApiError::NotRecorded { method, endpoint } => write!(f, "No recorded response for {method} {endpoint}"),
+		}
+	}
+}
+
+impl std::error::Error for ApiError {
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+		match self {
+			ApiError::Transport(err) => Some(err),
+			ApiError::Decode(err) => Some(err),
+			_ => None,
+		}
+	}
+}
+
+impl From<reqwest::Error> for ApiError {
+	fn from(err: reqwest::Error) -> Self {
+		ApiError::Transport(err)
+	}
+}
+
+impl From<serde_json::Error> for ApiError {
+	fn from(err: serde_json::Error) -> Self {
+		ApiError::Decode(err)
+	}
+}
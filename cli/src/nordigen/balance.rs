@@ -0,0 +1,54 @@
+use rust_decimal::Decimal;
+use crate::nordigen::api_error::ApiError;
+use crate::nordigen::client_credentials::ClientCredentials;
+use crate::nordigen::http_interface;
+use crate::nordigen::http_interface::ApiClient;
+use crate::nordigen::token::Token;
+use crate::nordigen::transaction::RawTransaction;
+
+// Booked balances first, as the fetched transactions are booked ones only.
+static BALANCE_TYPES: &[&str] = &["interimBooked", "closingBooked", "expected", "interimAvailable"];
+
+pub fn current_balance(client: &ApiClient, client_credentials: &ClientCredentials, token: &mut Token, account_id: &str, currency: &str) -> Result<Option<Decimal>, ApiError> {
+	let token = token.get_access_token(client, client_credentials)?;
+	let res = http_interface::accounts::balances::get(client, token, account_id)?;
+
+	let balance = BALANCE_TYPES.iter()
+		.find_map(|balance_type| res.balances.iter().find(|balance| balance.balance_type == *balance_type && balance.balance_amount.currency == currency))
+		.map(|balance| balance.balance_amount.amount);
+
+	Ok(balance)
+}
+
+// Every currency of the first balance type the bank gives, as an account can hold several.
+pub fn current_balances(client: &ApiClient, client_credentials: &ClientCredentials, token: &mut Token, account_id: &str) -> Result<Vec<(Decimal, String)>, ApiError> {
+	let token = token.get_access_token(client, client_credentials)?;
+	let res = http_interface::accounts::balances::get(client, token, account_id)?;
+
+	let Some(balance_type) = BALANCE_TYPES.iter().find(|balance_type| res.balances.iter().any(|balance| balance.balance_type == **balance_type)) else {
+		return Ok(vec![]);
+	};
+	let balances = res.balances.into_iter()
+		.filter(|balance| balance.balance_type == *balance_type)
+		.map(|balance| (balance.balance_amount.amount, balance.balance_amount.currency))
+		.collect();
+
+	Ok(balances)
+}
+
+// Walks back from the balance after the latest transaction, filling in the balance after each transaction the bank didn't give one for.
+// A balance the bank did give is taken as the new starting point. Transactions on the same day are taken by booking time,
+// or in the order the bank lists them when it gives none. Either way the balance at the end of each day is right.
+pub fn fill_running_balances(transactions: &mut [RawTransaction], current_balance: Option<Decimal>) {
+	let mut order = (0..transactions.len()).collect::<Vec<_>>();
+	order.sort_by_key(|index| transactions[*index].booking_order());
+
+	let mut balance_after = current_balance;
+	for index in order.into_iter().rev() {
+		let transaction = &mut transactions[index];
+		if transaction.balance.is_none() {
+			transaction.balance = balance_after;
+		}
+		balance_after = transaction.balance.map(|balance| balance - transaction.amount);
+	}
+}
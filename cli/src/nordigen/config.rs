@@ -1,12 +1,44 @@
+use std::path::PathBuf;
 use serde::{Deserialize, Serialize};
+use crate::matcher::config::MatcherConfig;
 use crate::nordigen::client_credentials::ClientCredentials;
 use crate::nordigen::institution::Institution;
 use crate::nordigen::token::Token;
+use crate::report::budget::Budget;
 
 #[derive(Debug, Default, Serialize, Deserialize)]
 pub struct Config {
 	pub client_credentials: Option<ClientCredentials>,
 	pub token: Option<Token>,
 	pub selected_institutions: Vec<Institution>,
+	#[serde(default)]
+	pub http: HttpConfig,
+	#[serde(default)]
+	pub matcher: MatcherConfig,
+	#[serde(default)]
+	pub budgets: Vec<Budget>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HttpConfig {
+	pub base_url: String,
+	pub proxy: Option<String>,
+	pub ca_certificates: Vec<PathBuf>,
+	pub timeout_seconds: Option<u64>,
+	pub connect_timeout_seconds: Option<u64>,
+	pub user_agent: Option<String>,
+}
+
+impl Default for HttpConfig {
+	fn default() -> Self {
+		HttpConfig {
+			base_url: "https://ob.nordigen.com/api/v2".into(),
+			proxy: None,
+			ca_certificates: vec![],
+			timeout_seconds: None,
+			connect_timeout_seconds: None,
+			user_agent: None,
+		}
+	}
+}
@@ -1,9 +1,20 @@
+use std::path::Path;
+use std::{fs, thread};
+use chrono::{Duration, Local};
 use color_eyre::eyre;
-use color_eyre::eyre::eyre;
-use reqwest::blocking::Client;
-use reqwest::header::HeaderValue;
-use reqwest::Url;
+use color_eyre::eyre::{eyre, WrapErr};
+use reqwest::blocking::{Client, Request, Response};
+use reqwest::header::{HeaderMap, HeaderValue};
+use reqwest::{Certificate, Method, Proxy, StatusCode, Url};
 use serde::{Deserialize, Serialize};
+use crate::nordigen::api_error::ApiError;
+use crate::nordigen::config::HttpConfig;
+use crate::nordigen::recording::{Recorder, Replayer};
+use crate::nordigen::rate_limit::RateLimit;
+
+const MAX_ATTEMPTS: u32 = 5;
+const INITIAL_BACKOFF_SECONDS: i64 = 1;
+const MAX_RATE_LIMIT_WAIT_SECONDS: i64 = 60;
 
 
 #[derive(Debug, Deserialize)]
@@ -14,30 +25,125 @@ pub struct PaginatedResult<E> {
 	pub results: Vec<E>,
 }
 
-fn build_url(endpoint: &str, id: Option<&str>) -> eyre::Result<Url> {
-	let mut url = Url::parse("https://ob.nordigen.com/api/v2")?;
+pub struct ApiClient {
+	client: Client,
+	base_url: Url,
+	traffic: Traffic,
+}
+
+enum Traffic {
+	Live,
+	Record(Recorder),
+	Replay(Replayer),
+}
+
+pub struct ApiResponse {
+	pub status: StatusCode,
+	pub headers: HeaderMap,
+	pub body: Vec<u8>,
+}
+
+impl ApiClient {
+	pub fn new(config: &HttpConfig) -> eyre::Result<ApiClient> {
+		let base_url = Url::parse(&config.base_url)
+			.wrap_err_with(|| format!("invalid base URL {}", config.base_url))?;
+		if base_url.cannot_be_a_base() {
+			return Err(eyre!("invalid base URL {}", config.base_url));
+		}
+
+		let mut default_headers = HeaderMap::new();
+		default_headers.insert("Accept", HeaderValue::from_static("application/json"));
+
+		let user_agent = config.user_agent.clone()
+			.unwrap_or_else(|| format!("{}/{}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION")));
+
+		let mut builder = Client::builder()
+			.default_headers(default_headers)
+			.user_agent(user_agent);
+
+		if let Some(proxy) = &config.proxy {
+			builder = builder.proxy(Proxy::all(proxy)?);
+		}
+
+		for path in &config.ca_certificates {
+			let pem = fs::read(path)
+				.wrap_err_with(|| format!("unable to read CA certificate {}", path.display()))?;
+			builder = builder.add_root_certificate(Certificate::from_pem(&pem)?);
+		}
+
+		if let Some(timeout_seconds) = config.timeout_seconds {
+			builder = builder.timeout(std::time::Duration::from_secs(timeout_seconds));
+		}
+
+		if let Some(connect_timeout_seconds) = config.connect_timeout_seconds {
+			builder = builder.connect_timeout(std::time::Duration::from_secs(connect_timeout_seconds));
+		}
+
+		Ok(ApiClient {
+			client: builder.build()?,
+			base_url,
+			traffic: Traffic::Live,
+		})
+	}
+
+	pub fn record_to(mut self, dir: &Path) -> eyre::Result<ApiClient> {
+		self.traffic = Traffic::Record(Recorder::new(dir)?);
+		Ok(self)
+	}
 
-	{
-		let mut path_segments = url.path_segments_mut()
-			.map_err(|_| eyre!("url not base"))?;
+	pub fn replay_from(mut self, dir: &Path) -> eyre::Result<ApiClient> {
+		self.traffic = Traffic::Replay(Replayer::new(dir)?);
+		Ok(self)
+	}
 
-		for segment in endpoint.split("/") {
-			path_segments.push(segment);
+	pub fn recorder(&self) -> Option<&Recorder> {
+		match &self.traffic {
+			Traffic::Record(recorder) => Some(recorder),
+			_ => None,
 		}
+	}
 
-		if let Some(id) = id {
-			path_segments.push(id);
+	pub fn replayer(&self) -> Option<&Replayer> {
+		match &self.traffic {
+			Traffic::Replay(replayer) => Some(replayer),
+			_ => None,
 		}
-		path_segments.push("");
 	}
 
-	Ok(url)
+	fn endpoint<'a>(&self, url: &'a Url) -> &'a str {
+		url.path()
+			.strip_prefix(self.base_url.path().trim_end_matches('/'))
+			.unwrap_or(url.path())
+			.trim_start_matches('/')
+	}
+
+	fn build_url(&self, endpoint: &str, id: Option<&str>) -> Result<Url, ApiError> {
+		let mut url = self.base_url.clone();
+
+		{
+			let mut path_segments = url.path_segments_mut()
+				.map_err(|_| ApiError::InvalidUrl(self.base_url.to_string()))?;
+
+			path_segments.pop_if_empty();
+
+			for segment in endpoint.split('/') {
+				path_segments.push(segment);
+			}
+
+			if let Some(id) = id {
+				path_segments.push(id);
+			}
+			path_segments.push("");
+		}
+
+		Ok(url)
+	}
 }
 
-fn post<Req: Serialize, Res: for<'de> Deserialize<'de>>(client: &Client, endpoint: &str, body: &Req, token: Option<&str>) -> eyre::Result<Res> {
+fn post<Req: Serialize, Res: for<'de> Deserialize<'de>>(client: &ApiClient, endpoint: &str, body: &Req, token: Option<&str>) -> Result<Res, ApiError> {
 	let req = {
-		let url = build_url(endpoint, None)?;
-		let mut builder = client.post(url)
+		let url = client.build_url(endpoint, None)?;
+		let mut builder = client.client.post(url)
 			.header("Content-Type", HeaderValue::from_static("application/json"))
 			.json(body);
 
@@ -49,24 +155,28 @@ fn post<Req: Serialize, Res: for<'de> Deserialize<'de>>(client: &Client, endpoin
 	};
 
 	let res = {
-		let res = client.execute(req)?;
-		let status = res.status();
+		let res = execute(client, req)?;
 
-		if !status.is_success() {
-			let body = res.text()?;
-			return Err(eyre!("[POST:{status}: {body}]"));
+		if !res.status.is_success() {
+			return Err(ApiError::from_response(res));
 		}
 
-		res.json()?
+		serde_json::from_slice(&res.body)?
 	};
 
 	Ok(res)
 }
 
-fn get<Res: for<'de> Deserialize<'de>>(client: &Client, endpoint: &str, token: Option<&str>, id: Option<&str>) -> eyre::Result<Res> {
+fn get<Res: for<'de> Deserialize<'de>>(client: &ApiClient, endpoint: &str, token: Option<&str>, id: Option<&str>) -> Result<Res, ApiError> {
+	let (res, _) = get_with_rate_limit(client, endpoint, token, id)?;
+
+	Ok(res)
+}
+
+fn get_with_rate_limit<Res: for<'de> Deserialize<'de>>(client: &ApiClient, endpoint: &str, token: Option<&str>, id: Option<&str>) -> Result<(Res, Option<RateLimit>), ApiError> {
 	let req = {
-		let url = build_url(endpoint, id)?;
-		let mut builder = client.get(url);
+		let url = client.build_url(endpoint, id)?;
+		let mut builder = client.client.get(url);
 
 		if let Some(token) = token {
 			builder = builder.bearer_auth(token);
@@ -76,28 +186,85 @@ fn get<Res: for<'de> Deserialize<'de>>(client: &Client, endpoint: &str, token: O
 	};
 
 	let res = {
-		let res = client.execute(req)?;
-		let status = res.status();
+		let res = execute(client, req)?;
+		let rate_limit = RateLimit::from_headers(&res.headers, Local::now());
 
-		if !status.is_success() {
-			let body = res.text()?;
-			return Err(eyre!("[{status}] GET {endpoint}: {body}"));
+		if !res.status.is_success() {
+			return Err(ApiError::from_response(res));
 		}
 
-		let json: serde_json::Value = res.json()?;
-		// eprintln!("[{endpoint}] {json:#?}");
+		(serde_json::from_slice(&res.body)?, rate_limit)
+	};
+
+	Ok(res)
+}
 
-		serde_json::from_value(json)?
+fn execute(client: &ApiClient, req: Request) -> Result<ApiResponse, ApiError> {
+	let method = req.method().to_string();
+	let endpoint = client.endpoint(req.url()).to_string();
+
+	if let Traffic::Replay(replayer) = &client.traffic {
+		return replayer.replay(&method, &endpoint);
+	}
+
+	let request_body = req.body()
+		.and_then(|body| body.as_bytes())
+		.map(|body| body.to_vec());
+
+	let res = execute_with_retries(client, req)?;
+	let res = ApiResponse {
+		status: res.status(),
+		headers: res.headers().clone(),
+		body: res.bytes()?.to_vec(),
 	};
 
+	if let Traffic::Record(recorder) = &client.traffic {
+		if let Err(err) = recorder.record(&method, &endpoint, request_body.as_deref(), &res) {
+			eprintln!("Unable to record {method} {endpoint}: {err}");
+		}
+	}
+
 	Ok(res)
 }
 
+// A POST that timed out or failed on the server may still have created a requisition, so it is only retried
+// when it was turned away before being handled, as with a failed connection or the rate limit.
+fn execute_with_retries(client: &ApiClient, req: Request) -> Result<Response, ApiError> {
+	let mut backoff = Duration::seconds(INITIAL_BACKOFF_SECONDS);
+	let is_idempotent = req.method() == Method::GET;
+
+	for _ in 1..MAX_ATTEMPTS {
+		let Some(attempt) = req.try_clone() else { break };
+
+		let wait = match client.client.execute(attempt) {
+			Ok(res) if res.status().is_server_error() && is_idempotent => backoff,
+			Ok(res) if res.status() == StatusCode::TOO_MANY_REQUESTS => {
+				let time_til_reset = RateLimit::from_headers(res.headers(), Local::now())
+					.map(|rate_limit| rate_limit.time_til_reset());
+
+				match time_til_reset {
+					Some(time_til_reset) if time_til_reset <= Duration::seconds(MAX_RATE_LIMIT_WAIT_SECONDS) => time_til_reset,
+					_ => return Ok(res),
+				}
+			},
+			Ok(res) => return Ok(res),
+			Err(err) if err.is_connect() || (err.is_timeout() && is_idempotent) => backoff,
+			Err(err) => return Err(ApiError::Transport(err)),
+		};
+
+		eprintln!("Request to {} failed, retrying in {} seconds", req.url(), wait.num_seconds());
+		thread::sleep(wait.to_std().unwrap_or_default());
+		backoff = backoff * 2;
+	}
+
+	Ok(client.client.execute(req)?)
+}
+
 pub mod accounts {
 	pub mod details {
-		use color_eyre::eyre;
-		use reqwest::blocking::Client;
+		use crate::nordigen::http_interface::ApiClient;
 		use serde::Deserialize;
+		use crate::nordigen::api_error::ApiError;
 		use crate::nordigen::http_interface;
 
 		#[derive(Debug, Deserialize)]
@@ -112,10 +279,12 @@ pub mod accounts {
 			pub status: String,
 			pub name: Option<String>,
 			#[serde(rename="displayName")]
-			pub display_name: Option<String>
+			pub display_name: Option<String>,
+			#[serde(rename="ownerName")]
+			pub owner_name: Option<String>,
 		}
 
-		pub fn get(client: &Client, token: &str, account_id: &str) -> eyre::Result<GetResponseBody> {
+		pub fn get(client: &ApiClient, token: &str, account_id: &str) -> Result<GetResponseBody, ApiError> {
 			let endpoint = format!("accounts/{account_id}/details");
 			http_interface::get(client, &endpoint, Some(token), None)
 		}
@@ -123,11 +292,12 @@ pub mod accounts {
 
 	pub mod transactions {
 		use chrono::NaiveDate;
-		use color_eyre::eyre;
-		use reqwest::blocking::Client;
+		use crate::nordigen::http_interface::ApiClient;
 		use rust_decimal::Decimal;
 		use serde::Deserialize;
+		use crate::nordigen::api_error::ApiError;
 		use crate::nordigen::http_interface;
+		use crate::nordigen::rate_limit::RateLimit;
 
 		#[derive(Debug, Deserialize)]
 		pub struct GetResponseBody {
@@ -143,12 +313,42 @@ pub mod accounts {
 		pub struct BookedTransaction {
 			#[serde(rename = "valueDate")]
 			pub value_date: NaiveDate,
+			#[serde(rename = "bookingDateTime")]
+			pub booking_date_time: Option<String>,
 			#[serde(rename = "transactionAmount")]
 			pub transaction_amount: Amount,
 			#[serde(rename = "transactionId")]
 			pub transaction_id: String,
 			#[serde(rename = "additionalInformation")]
 			pub additional_information: Option<String>,
+			#[serde(rename = "remittanceInformationUnstructured")]
+			pub remittance_information_unstructured: Option<String>,
+			#[serde(rename = "currencyExchange", default)]
+			pub currency_exchange: Option<CurrencyExchanges>,
+			#[serde(rename = "creditorName")]
+			pub creditor_name: Option<String>,
+			#[serde(rename = "creditorAccount")]
+			pub creditor_account: Option<AccountReference>,
+			#[serde(rename = "debtorName")]
+			pub debtor_name: Option<String>,
+			#[serde(rename = "debtorAccount")]
+			pub debtor_account: Option<AccountReference>,
+			#[serde(rename = "merchantCategoryCode")]
+			pub merchant_category_code: Option<String>,
+			#[serde(rename = "balanceAfterTransaction")]
+			pub balance_after_transaction: Option<Balance>,
+		}
+
+		#[derive(Debug, Deserialize)]
+		pub struct Balance {
+			#[serde(rename = "balanceAmount")]
+			pub balance_amount: Amount,
+		}
+
+		#[derive(Debug, Deserialize)]
+		pub struct AccountReference {
+			pub iban: Option<String>,
+			pub bban: Option<String>,
 		}
 
 		#[derive(Debug, Deserialize)]
@@ -157,8 +357,56 @@ pub mod accounts {
 			pub currency: String,
 		}
 
-		pub fn get(client: &Client, token: &str, account_id: &str) -> eyre::Result<GetResponseBody> {
+		#[derive(Debug, Deserialize)]
+		#[serde(untagged)]
+		pub enum CurrencyExchanges {
+			One(CurrencyExchange),
+			Many(Vec<CurrencyExchange>),
+		}
+
+		#[derive(Debug, Deserialize)]
+		pub struct CurrencyExchange {
+			#[serde(rename = "sourceCurrency")]
+			pub source_currency: String,
+			#[serde(rename = "targetCurrency")]
+			pub target_currency: String,
+			#[serde(rename = "unitCurrency")]
+			pub unit_currency: Option<String>,
+			#[serde(rename = "exchangeRate")]
+			pub exchange_rate: Decimal,
+		}
+
+		pub fn get(client: &ApiClient, token: &str, account_id: &str) -> Result<(GetResponseBody, Option<RateLimit>), ApiError> {
 			let endpoint = format!("accounts/{account_id}/transactions");
+			http_interface::get_with_rate_limit(client, &endpoint, Some(token), None)
+		}
+	}
+
+	pub mod balances {
+		use chrono::NaiveDate;
+		use serde::Deserialize;
+		use crate::nordigen::api_error::ApiError;
+		use crate::nordigen::http_interface;
+		use crate::nordigen::http_interface::ApiClient;
+		use crate::nordigen::http_interface::accounts::transactions::Amount;
+
+		#[derive(Debug, Deserialize)]
+		pub struct GetResponseBody {
+			pub balances: Vec<Balance>,
+		}
+
+		#[derive(Debug, Deserialize)]
+		pub struct Balance {
+			#[serde(rename = "balanceAmount")]
+			pub balance_amount: Amount,
+			#[serde(rename = "balanceType")]
+			pub balance_type: String,
+			#[serde(rename = "referenceDate")]
+			pub reference_date: Option<NaiveDate>,
+		}
+
+		pub fn get(client: &ApiClient, token: &str, account_id: &str) -> Result<GetResponseBody, ApiError> {
+			let endpoint = format!("accounts/{account_id}/balances");
 			http_interface::get(client, &endpoint, Some(token), None)
 		}
 	}
@@ -204,9 +452,9 @@ pub mod agreements {
 }
 
 pub mod institutions {
-	use color_eyre::eyre;
-	use reqwest::blocking::Client;
+	use crate::nordigen::http_interface::ApiClient;
 	use serde::Deserialize;
+	use crate::nordigen::api_error::ApiError;
 	use crate::nordigen::http_interface;
 
 	#[derive(Debug, Deserialize)]
@@ -219,16 +467,16 @@ pub mod institutions {
 		pub logo: String,
 	}
 
-	pub fn list(client: &Client, token: &str) -> eyre::Result<Vec<GetResponseBody>> {
+	pub fn list(client: &ApiClient, token: &str) -> Result<Vec<GetResponseBody>, ApiError> {
 		http_interface::get(client, "institutions", Some(token), None)
 	}
 }
 
 pub mod requisitions {
 	use chrono::{DateTime, Local};
-	use color_eyre::eyre;
-	use reqwest::blocking::Client;
+	use crate::nordigen::http_interface::ApiClient;
 	use serde::{Deserialize, Serialize};
+	use crate::nordigen::api_error::ApiError;
 	use crate::nordigen::http_interface;
 
 	#[derive(Debug, Deserialize)]
@@ -242,7 +490,7 @@ pub mod requisitions {
 		pub link: String,
 	}
 
-	pub fn get(client: &Client, token: &str, id: &str) -> eyre::Result<GetResponseBody> {
+	pub fn get(client: &ApiClient, token: &str, id: &str) -> Result<GetResponseBody, ApiError> {
 		http_interface::get(client, "requisitions", Some(token), Some(id))
 	}
 
@@ -264,17 +512,17 @@ pub mod requisitions {
 		pub link: String,
 	}
 
-	pub fn post(client: &Client, token: &str, body: &PostRequestBody) -> eyre::Result<PostResponseBody> {
+	pub fn post(client: &ApiClient, token: &str, body: &PostRequestBody) -> Result<PostResponseBody, ApiError> {
 		http_interface::post(client, "requisitions", body, Some(token))
 	}
 }
 
 pub mod token {
 	pub mod new {
-		use color_eyre::eyre;
-		use reqwest::blocking::Client;
+		use crate::nordigen::http_interface::ApiClient;
 		use serde::{Deserialize, Serialize};
 		use crate::nordigen::client_credentials::ClientCredentials;
+		use crate::nordigen::api_error::ApiError;
 		use crate::nordigen::http_interface;
 
 		#[derive(Debug, Serialize)]
@@ -300,15 +548,15 @@ pub mod token {
 			pub refresh_expires: i64,
 		}
 
-		pub fn post(client: &Client, body: &PostRequestBody) -> eyre::Result<PostResponseBody> {
+		pub fn post(client: &ApiClient, body: &PostRequestBody) -> Result<PostResponseBody, ApiError> {
 			http_interface::post(client, "token/new", body, None)
 		}
 	}
 
 	pub mod refresh {
-		use color_eyre::eyre;
-		use reqwest::blocking::Client;
+		use crate::nordigen::http_interface::ApiClient;
 		use serde::{Deserialize, Serialize};
+		use crate::nordigen::api_error::ApiError;
 		use crate::nordigen::http_interface;
 
 		#[derive(Debug, Serialize)]
@@ -322,7 +570,7 @@ pub mod token {
 			pub access_expires: i64,
 		}
 
-		pub fn post(client: &Client, body: &PostRequestBody) -> eyre::Result<PostResponseBody> {
+		pub fn post(client: &ApiClient, body: &PostRequestBody) -> Result<PostResponseBody, ApiError> {
 			http_interface::post(client, "token/refresh", body, None)
 		}
 	}
@@ -1,10 +1,11 @@
 use std::collections::{HashMap, HashSet};
 use serde::{Deserialize, Serialize};
 use std::fmt::{Display, Formatter};
-use color_eyre::eyre;
 use crate::nordigen::client_credentials::ClientCredentials;
-use crate::HTTP_CLIENT;
+use crate::nordigen::api_error::ApiError;
 use crate::nordigen::http_interface;
+use crate::nordigen::http_interface::ApiClient;
+use crate::nordigen::rate_limit::RateLimit;
 use crate::nordigen::requisition::Requisition;
 use crate::nordigen::token::Token;
 
@@ -18,13 +19,15 @@ pub struct Institution {
 	pub countries: Vec<String>,
 	pub requisition_id: Option<String>,
 	pub observed_transactions: HashMap<AccountID, HashSet<TransactionID>>,
+	#[serde(default)]
+	pub transaction_rate_limits: HashMap<AccountID, RateLimit>,
 }
 
 impl Institution {
-	pub fn list(client_credentials: &ClientCredentials, token: &mut Token) -> eyre::Result<Vec<Institution>> {
-		let access_token = token.get_access_token(client_credentials)?;
+	pub fn list(client: &ApiClient, client_credentials: &ClientCredentials, token: &mut Token) -> Result<Vec<Institution>, ApiError> {
+		let access_token = token.get_access_token(client, client_credentials)?;
 
-		let response = http_interface::institutions::list(&HTTP_CLIENT, access_token)?;
+		let response = http_interface::institutions::list(client, access_token)?;
 		let response = response.into_iter()
 			.map(|res| Institution {
 				id: res.id,
@@ -32,18 +35,25 @@ impl Institution {
 				countries: res.countries,
 				requisition_id: None,
 				observed_transactions: HashMap::new(),
+				transaction_rate_limits: HashMap::new(),
 			})
 			.collect();
 
 		Ok(response)
 	}
 
-	pub fn get_requisition(&mut self, client_credentials: &ClientCredentials, token: &mut Token) -> eyre::Result<Requisition> {
+	pub fn get_requisition(&mut self, client: &ApiClient, client_credentials: &ClientCredentials, token: &mut Token) -> Result<Requisition, ApiError> {
 		let req = if let Some(requisition_id) = &self.requisition_id {
-			Requisition::get(client_credentials, token, requisition_id)
-				.or_else(|_| Requisition::new(client_credentials, token, &self.id))?
+			match Requisition::get(client, client_credentials, token, requisition_id) {
+				Ok(req) => req,
+				Err(err @ (ApiError::NotFound { .. } | ApiError::AccessExpired { .. })) => {
+					eprintln!("Linking {self} again, the previous link is no longer usable: {err}");
+					Requisition::new(client, client_credentials, token, &self.id)?
+				},
+				Err(err) => return Err(err),
+			}
 		} else {
-			Requisition::new(client_credentials, token, &self.id)?
+			Requisition::new(client, client_credentials, token, &self.id)?
 		};
 
 		self.requisition_id = Some(req.id.clone());
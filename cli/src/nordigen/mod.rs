@@ -1,12 +1,23 @@
+use std::collections::HashSet;
+use std::path::PathBuf;
 use std::rc::Rc;
+use chrono::Local;
 use color_eyre::eyre;
 use color_eyre::eyre::eyre;
+use rust_decimal::Decimal;
 use crate::{APP_NAME, interactions};
 use crate::nordigen::account::Account;
-use crate::nordigen::config::Config;
+use crate::nordigen::client_credentials::ClientCredentials;
+use crate::nordigen::api_error::ApiError;
+use crate::matcher::config::MatcherConfig;
+use crate::nordigen::config::{Config, HttpConfig};
+use crate::nordigen::http_interface::ApiClient;
 use crate::nordigen::institution::Institution;
+use crate::nordigen::rate_limit::RateLimit;
+use crate::nordigen::requisition::Requisition;
 use crate::nordigen::token::Token;
 use crate::nordigen::transaction::RawTransaction;
+use crate::report::budget::Budget;
 
 pub mod http_interface;
 pub mod config;
@@ -16,60 +27,175 @@ pub mod institution;
 mod requisition;
 pub mod transaction;
 pub mod account;
+pub mod rate_limit;
+pub mod api_error;
+pub mod recording;
+pub mod balance;
 
 
-pub fn get_raw_transactions() -> eyre::Result<Vec<(RawTransaction, Rc<Account>)>> {
-	let loaded_config = confy::load::<Config>(APP_NAME, Some("config"))?;
+pub struct AccountBalances {
+	pub account: Rc<Account>,
+	pub amounts: Vec<(Decimal, String)>,
+}
 
-	let client_credentials = if let Some(client_credentials) =  loaded_config.client_credentials {
-		client_credentials
-	} else {
-		interactions::ClientCredentialsInput::prompt()?
-	};
+pub struct FetchOptions {
+	pub force: bool,
+	pub reuse_institutions: bool,
+	pub base_url: Option<String>,
+	pub record: Option<PathBuf>,
+	pub replay: Option<PathBuf>,
+	// Returns every fetched transaction and leaves the observed transactions as they were, for commands that only look
+	// at the transactions so the next export still gets them. The calls it makes still count against the rate limits.
+	pub read_only: bool,
+}
 
-	let mut token: Token = Token::new(&client_credentials)?;
+// The linked requisitions of every selected institution, with what is needed to call the API for their accounts.
+struct Session {
+	client: ApiClient,
+	client_credentials: ClientCredentials,
+	token: Token,
+	institutions: Vec<Institution>,
+	requisitions: Vec<Requisition>,
+	http: HttpConfig,
+	matcher: MatcherConfig,
+	budgets: Vec<Budget>,
+}
 
-	let reuse_selected_institutions = interactions::ReuseConfirm::new(&loaded_config.selected_institutions).prompt()?;
-	let mut selected_institutions = if reuse_selected_institutions {
-		loaded_config.selected_institutions
-	} else {
-		let available_institutions = Institution::list(&client_credentials, &mut token)?;
+impl Session {
+	fn open(loaded_config: Config, options: &FetchOptions) -> eyre::Result<Session> {
+		let Config { client_credentials, selected_institutions, http, matcher, budgets, .. } = loaded_config;
+		let mut http_config = http.clone();
+		if let Some(base_url) = &options.base_url {
+			http_config.base_url = base_url.clone();
+		}
+		let mut client = ApiClient::new(&http_config)?;
+		if let Some(dir) = &options.record {
+			client = client.record_to(dir)?;
+		}
+		if let Some(dir) = &options.replay {
+			client = client.replay_from(dir)?;
+		}
 
-		interactions::InstitutionSelect::new(available_institutions)
-			.prompt()?
-	};
+		let client_credentials = if client.replayer().is_some() {
+			ClientCredentials { id: "replay".into(), secret: "replay".into() }
+		} else if let Some(client_credentials) = client_credentials {
+			client_credentials
+		} else {
+			interactions::ClientCredentialsInput::prompt()?
+		};
 
-	let mut requisitions = selected_institutions.iter_mut()
-		.map(|si| si.get_requisition(&client_credentials, &mut token))
-		.collect::<Result<Vec<_>, _ >>()?;
+		let mut token: Token = Token::new(&client, &client_credentials)?;
 
-	for (index, requisition) in requisitions.iter_mut().enumerate() {
-		if requisition.is_linked() {
-			continue;
+		let mut institutions = if let Some(replayer) = client.replayer() {
+			replayer.institutions()?
+		} else {
+			select_institutions(&client, &client_credentials, &mut token, options, selected_institutions)?
+		};
+
+		if let Some(recorder) = client.recorder() {
+			recorder.record_institutions(&institutions)?;
 		}
 
-		requisition.open_link()?;
-		interactions::AcceptedConfirm::new(&selected_institutions[index]).prompt()?;
-		requisition.update(&client_credentials, &mut token)?;
+		let mut requisitions = institutions.iter_mut()
+			.map(|si| si.get_requisition(&client, &client_credentials, &mut token))
+			.collect::<Result<Vec<_>, _ >>()?;
+
+		for (index, requisition) in requisitions.iter_mut().enumerate() {
+			if requisition.is_linked() {
+				continue;
+			}
+
+			if client.replayer().is_none() {
+				requisition.open_link()?;
+				interactions::AcceptedConfirm::new(&institutions[index]).prompt()?;
+			}
+			requisition.update(&client, &client_credentials, &mut token)?;
 
-		if !requisition.is_linked() {
-			Err(eyre!("Account still unlinked after returning!"))?;
+			if !requisition.is_linked() {
+				Err(eyre!("Account still unlinked after returning!"))?;
+			}
 		}
+
+		Ok(Session { client, client_credentials, token, institutions, requisitions, http, matcher, budgets })
+	}
+
+	// A replay leaves the config as it was, as nothing in it came from the bank.
+	fn save(self) -> eyre::Result<()> {
+		if self.client.replayer().is_some() {
+			return Ok(());
+		}
+
+		let save_config = Config {
+			client_credentials: Some(self.client_credentials),
+			token: Some(self.token),
+			selected_institutions: self.institutions,
+			http: self.http,
+			matcher: self.matcher,
+			budgets: self.budgets,
+		};
+
+		confy::store(APP_NAME, Some("config"), save_config)?;
+		Ok(())
 	}
+}
+
+pub fn get_raw_transactions(loaded_config: Config, options: &FetchOptions) -> eyre::Result<Vec<(RawTransaction, Rc<Account>)>> {
+	let mut session = Session::open(loaded_config, options)?;
+	let Session { client, client_credentials, token, institutions, requisitions, .. } = &mut session;
 
 	let mut transactions = vec![];
 
 	for (institution_index, requisition) in requisitions.iter().enumerate() {
-		let institution = &mut selected_institutions[institution_index];
+		let institution = &mut institutions[institution_index];
 		for account in requisition.accounts.iter() {
-			let account_transactions = match RawTransaction::list_in_account(&client_credentials, &mut token, &account.id) {
-				Ok(transactions) => transactions,
+			if let Some(rate_limit) = institution.transaction_rate_limits.get(&account.id).filter(|_| client.replayer().is_none()) {
+				if rate_limit.is_exhausted() || (rate_limit.is_last_call() && !options.force) {
+					eprintln!("Skipping {account} to preserve the daily API quota ({rate_limit}), use --force to fetch anyway");
+					continue
+				}
+			}
+
+			let mut account_transactions = match RawTransaction::list_in_account(client, client_credentials, token, &account.id) {
+				Ok((transactions, rate_limit)) => {
+					if let Some(rate_limit) = rate_limit {
+						eprintln!("{account}: {rate_limit}");
+						institution.transaction_rate_limits.insert(account.id.clone(), rate_limit);
+					}
+					transactions
+				},
+				Err(ApiError::RateLimited { retry_after: Some(retry_after), detail }) => {
+					let rate_limit = institution.transaction_rate_limits.entry(account.id.clone())
+						.or_insert(RateLimit { limit: 0, remaining: 0, resets_at: Local::now() });
+					rate_limit.remaining = 0;
+					rate_limit.resets_at = Local::now() + retry_after;
+					eprintln!("Daily API quota for {account} is spent ({detail}), skipping until {}", rate_limit.resets_at.format("%Y-%m-%d %H:%M"));
+					continue
+				},
+				Err(err @ ApiError::AccessExpired { .. }) => {
+					eprintln!("Unable to fetch transactions for {account}, {err}");
+					institution.requisition_id = None;
+					continue
+				},
 				Err(err) => {
 					eprintln!("Error while fetching transactions for {account}\n{err}");
 					continue
 				},
 			};
 
+			let currencies = account_transactions.iter().map(|transaction| transaction.currency.as_str()).collect::<HashSet<_>>();
+			let is_missing_balances = account_transactions.iter().any(|transaction| transaction.balance.is_none());
+			if let (true, [currency]) = (is_missing_balances, currencies.into_iter().collect::<Vec<_>>().as_slice()) {
+				match balance::current_balance(client, client_credentials, token, &account.id, currency) {
+					Ok(current_balance) => balance::fill_running_balances(&mut account_transactions, current_balance),
+					Err(err) => eprintln!("Unable to fetch the balance of {account}, exporting without running balances\n{err}"),
+				}
+			}
+
+			if options.read_only {
+				transactions.extend(account_transactions.into_iter().map(|transaction| (transaction, Rc::new(account.clone()))));
+				continue;
+			}
+
 			for transaction in account_transactions.into_iter() {
 				let observed_transactions = institution.observed_transactions
 					.entry(account.id.clone())
@@ -83,13 +209,58 @@ pub fn get_raw_transactions() -> eyre::Result<Vec<(RawTransaction, Rc<Account>)>
 		}
 	}
 
-	let save_config = Config {
-		client_credentials: Some(client_credentials),
-		token: Some(token),
-		selected_institutions,
+	session.save()?;
+
+	Ok(transactions)
+}
+
+// The current balances of every account of every selected institution.
+// A total without some of the accounts is worse than none, so it fails naming the accounts that couldn't be fetched.
+pub fn get_balances(loaded_config: Config, options: &FetchOptions) -> eyre::Result<Vec<AccountBalances>> {
+	let mut session = Session::open(loaded_config, options)?;
+	let Session { client, client_credentials, token, institutions, requisitions, .. } = &mut session;
+
+	let mut balances = vec![];
+	let mut failed = vec![];
+
+	for (institution_index, requisition) in requisitions.iter().enumerate() {
+		for account in requisition.accounts.iter() {
+			match balance::current_balances(client, client_credentials, token, &account.id) {
+				Ok(amounts) => balances.push(AccountBalances { account: Rc::new(account.clone()), amounts }),
+				Err(err @ ApiError::AccessExpired { .. }) => {
+					eprintln!("Unable to fetch the balance of {account}, {err}");
+					institutions[institution_index].requisition_id = None;
+					failed.push(account.to_string());
+				},
+				Err(err) => {
+					eprintln!("Error while fetching the balance of {account}\n{err}");
+					failed.push(account.to_string());
+				},
+			}
+		}
+	}
+
+	session.save()?;
+
+	if !failed.is_empty() {
+		return Err(eyre!("Unable to fetch the balance of {}, so no net worth is taken", failed.join(", ")));
+	}
+	Ok(balances)
+}
+
+fn select_institutions(client: &ApiClient, client_credentials: &ClientCredentials, token: &mut Token, options: &FetchOptions, previously_selected: Vec<Institution>) -> eyre::Result<Vec<Institution>> {
+	let reuse_selected_institutions = if options.reuse_institutions && !previously_selected.is_empty() {
+		true
+	} else {
+		interactions::ReuseConfirm::new(&previously_selected).prompt()?
 	};
 
-	confy::store(APP_NAME, Some("config"), save_config)?;
+	if reuse_selected_institutions {
+		return Ok(previously_selected);
+	}
 
-	Ok(transactions)
+	let available_institutions = Institution::list(client, client_credentials, token)?;
+
+	interactions::InstitutionSelect::new(available_institutions)
+		.prompt()
 }
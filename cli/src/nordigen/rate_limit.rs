@@ -0,0 +1,64 @@
+use std::fmt::{Display, Formatter};
+use chrono::{DateTime, Duration, Local};
+use reqwest::header::HeaderMap;
+use serde::{Deserialize, Serialize};
+
+static ACCOUNT_LIMIT_HEADER: &str = "HTTP_X_RATELIMIT_ACCOUNT_SUCCESS_LIMIT";
+static ACCOUNT_REMAINING_HEADER: &str = "HTTP_X_RATELIMIT_ACCOUNT_SUCCESS_REMAINING";
+static ACCOUNT_RESET_HEADER: &str = "HTTP_X_RATELIMIT_ACCOUNT_SUCCESS_RESET";
+static LIMIT_HEADER: &str = "HTTP_X_RATELIMIT_LIMIT";
+static REMAINING_HEADER: &str = "HTTP_X_RATELIMIT_REMAINING";
+static RESET_HEADER: &str = "HTTP_X_RATELIMIT_RESET";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RateLimit {
+	pub limit: u64,
+	pub remaining: u64,
+	pub resets_at: DateTime<Local>,
+}
+
+impl RateLimit {
+	// The per account limits are preferred, as those are the daily limits that are easily exhausted.
+	pub fn from_headers(headers: &HeaderMap, received_at: DateTime<Local>) -> Option<RateLimit> {
+		RateLimit::parse(headers, received_at, ACCOUNT_LIMIT_HEADER, ACCOUNT_REMAINING_HEADER, ACCOUNT_RESET_HEADER)
+			.or_else(|| RateLimit::parse(headers, received_at, LIMIT_HEADER, REMAINING_HEADER, RESET_HEADER))
+	}
+
+	fn parse(headers: &HeaderMap, received_at: DateTime<Local>, limit: &str, remaining: &str, reset: &str) -> Option<RateLimit> {
+		let header = |name: &str| -> Option<u64> {
+			headers.get(name)?.to_str().ok()?.trim().parse().ok()
+		};
+
+		Some(RateLimit {
+			limit: header(limit)?,
+			remaining: header(remaining)?,
+			resets_at: received_at + Duration::seconds(header(reset)? as i64),
+		})
+	}
+
+	pub fn time_til_reset(&self) -> Duration {
+		(self.resets_at - Local::now()).max(Duration::zero())
+	}
+
+	pub fn is_active(&self) -> bool {
+		Local::now() < self.resets_at
+	}
+
+	pub fn is_exhausted(&self) -> bool {
+		self.is_active() && self.remaining == 0
+	}
+
+	pub fn is_last_call(&self) -> bool {
+		self.is_active() && self.remaining <= 1
+	}
+}
+
+impl Display for RateLimit {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		if self.is_active() {
+			write!(f, "{}/{} calls remaining, resets at {}", self.remaining, self.limit, self.resets_at.format("%Y-%m-%d %H:%M"))
+		} else {
+			write!(f, "{} calls available", self.limit)
+		}
+	}
+}
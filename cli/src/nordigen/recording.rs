@@ -0,0 +1,230 @@
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fs;
+use std::path::{Path, PathBuf};
+use color_eyre::eyre;
+use color_eyre::eyre::WrapErr;
+use regex::Regex;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use crate::nordigen::api_error::ApiError;
+use crate::nordigen::http_interface::ApiResponse;
+use crate::nordigen::institution::Institution;
+
+static INSTITUTIONS_FILE: &str = "institutions.json";
+static SECRET_KEYS: [&str; 4] = ["secret_id", "secret_key", "access", "refresh"];
+static ACCOUNT_NUMBER_KEYS: [&str; 3] = ["iban", "bban", "maskedPan"];
+static REDACTED: &str = "REDACTED";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Exchange {
+	method: String,
+	endpoint: String,
+	request: Option<Value>,
+	status: u16,
+	headers: BTreeMap<String, String>,
+	body: Value,
+}
+
+pub struct Recorder {
+	dir: PathBuf,
+	sequence: RefCell<usize>,
+	pseudonyms: RefCell<HashMap<String, String>>,
+	iban_pattern: Regex,
+}
+
+impl Recorder {
+	pub fn new(dir: &Path) -> eyre::Result<Recorder> {
+		fs::create_dir_all(dir)
+			.wrap_err_with(|| format!("unable to create recording directory {}", dir.display()))?;
+
+		Ok(Recorder {
+			dir: dir.to_path_buf(),
+			sequence: RefCell::new(0),
+			pseudonyms: RefCell::new(HashMap::new()),
+			iban_pattern: Regex::new(r"\b[A-Z]{2}[0-9]{2}(?: ?[A-Z0-9]{4}){2,7}(?: ?[A-Z0-9]{1,4})?\b")?,
+		})
+	}
+
+	// Institutions are captured before any requisitions are made, so a replay walks the same path through the API.
+	pub fn record_institutions(&self, institutions: &[Institution]) -> eyre::Result<()> {
+		let path = self.dir.join(INSTITUTIONS_FILE);
+		fs::write(&path, serde_json::to_string_pretty(institutions)?)
+			.wrap_err_with(|| format!("unable to write {}", path.display()))
+	}
+
+	pub fn record(&self, method: &str, endpoint: &str, request: Option<&[u8]>, response: &ApiResponse) -> eyre::Result<()> {
+		let headers = response.headers.iter()
+			.filter(|(name, _)| *name != "set-cookie")
+			.filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+			.collect();
+
+		let exchange = Exchange {
+			method: method.to_string(),
+			endpoint: endpoint.to_string(),
+			request: request.map(|request| self.redact(parse_body(request))),
+			status: response.status.as_u16(),
+			headers,
+			body: self.redact(parse_body(&response.body)),
+		};
+
+		let sequence = {
+			let mut sequence = self.sequence.borrow_mut();
+			*sequence += 1;
+			*sequence
+		};
+		let slug = endpoint.trim_matches('/').replace('/', "_");
+		let path = self.dir.join(format!("{sequence:04}-{method}-{slug}.json"));
+
+		fs::write(&path, serde_json::to_string_pretty(&exchange)?)
+			.wrap_err_with(|| format!("unable to write {}", path.display()))
+	}
+
+	fn redact(&self, mut value: Value) -> Value {
+		self.collect_account_numbers(&value);
+		self.redact_value(&mut value);
+		value
+	}
+
+	fn collect_account_numbers(&self, value: &Value) {
+		match value {
+			Value::Object(object) => for (key, value) in object {
+				match value {
+					Value::String(account_number) if ACCOUNT_NUMBER_KEYS.contains(&key.as_str()) => {
+						self.pseudonym(account_number);
+					},
+					value => self.collect_account_numbers(value),
+				}
+			},
+			Value::Array(values) => values.iter().for_each(|value| self.collect_account_numbers(value)),
+			_ => {},
+		}
+	}
+
+	fn redact_value(&self, value: &mut Value) {
+		match value {
+			Value::Object(object) => for (key, value) in object.iter_mut() {
+				match value {
+					Value::String(secret) if SECRET_KEYS.contains(&key.as_str()) => *secret = REDACTED.to_string(),
+					value => self.redact_value(value),
+				}
+			},
+			Value::Array(values) => values.iter_mut().for_each(|value| self.redact_value(value)),
+			Value::String(text) => *text = self.redact_text(text),
+			_ => {},
+		}
+	}
+
+	fn redact_text(&self, text: &str) -> String {
+		// Structured creditor references (RF18 5390 0754 7034) are shaped like an IBAN, but only identify a payment.
+		let mut text = self.iban_pattern
+			.replace_all(text, |captures: &regex::Captures| match &captures[0] {
+				reference if reference.starts_with("RF") => reference.to_string(),
+				account_number => self.pseudonym(account_number),
+			})
+			.into_owned();
+
+		for (account_number, pseudonym) in self.pseudonyms.borrow().iter() {
+			text = text.replace(account_number, pseudonym);
+		}
+
+		text
+	}
+
+	// The same account number is always replaced by the same pseudonym, so transfers can still be matched in a replay.
+	fn pseudonym(&self, account_number: &str) -> String {
+		if account_number.starts_with(REDACTED) {
+			return account_number.to_string();
+		}
+
+		let mut pseudonyms = self.pseudonyms.borrow_mut();
+		let next = pseudonyms.len() + 1;
+		pseudonyms.entry(account_number.to_string())
+			.or_insert_with(|| format!("{REDACTED}{next:06}"))
+			.clone()
+	}
+}
+
+pub struct Replayer {
+	dir: PathBuf,
+	exchanges: RefCell<HashMap<(String, String), VecDeque<Exchange>>>,
+}
+
+impl Replayer {
+	pub fn new(dir: &Path) -> eyre::Result<Replayer> {
+		let mut paths = fs::read_dir(dir)
+			.wrap_err_with(|| format!("unable to read recording directory {}", dir.display()))?
+			.map(|entry| entry.map(|entry| entry.path()))
+			.collect::<Result<Vec<_>, _>>()?;
+		paths.sort();
+
+		let mut exchanges: HashMap<_, VecDeque<_>> = HashMap::new();
+		for path in paths {
+			let is_exchange = path.file_name()
+				.and_then(|name| name.to_str())
+				.is_some_and(|name| name != INSTITUTIONS_FILE && name.ends_with(".json"));
+			if !is_exchange {
+				continue;
+			}
+
+			let content = fs::read_to_string(&path)?;
+			let exchange: Exchange = serde_json::from_str(&content)
+				.wrap_err_with(|| format!("invalid recording {}", path.display()))?;
+
+			exchanges.entry((exchange.method.clone(), exchange.endpoint.clone()))
+				.or_default()
+				.push_back(exchange);
+		}
+
+		Ok(Replayer {
+			dir: dir.to_path_buf(),
+			exchanges: RefCell::new(exchanges),
+		})
+	}
+
+	pub fn institutions(&self) -> eyre::Result<Vec<Institution>> {
+		let path = self.dir.join(INSTITUTIONS_FILE);
+		let content = fs::read_to_string(&path)
+			.wrap_err_with(|| format!("unable to read {}", path.display()))?;
+
+		Ok(serde_json::from_str(&content)?)
+	}
+
+	// Exchanges to the same endpoint are served in recorded order, the last one is repeated once the rest are used up.
+	pub fn replay(&self, method: &str, endpoint: &str) -> Result<ApiResponse, ApiError> {
+		let not_recorded = || ApiError::NotRecorded { method: method.to_string(), endpoint: endpoint.to_string() };
+
+		let exchange = {
+			let mut exchanges = self.exchanges.borrow_mut();
+			let queue = exchanges.get_mut(&(method.to_string(), endpoint.to_string()))
+				.ok_or_else(not_recorded)?;
+
+			let exchange = if queue.len() > 1 { queue.pop_front() } else { queue.front().cloned() };
+			exchange.ok_or_else(not_recorded)?
+		};
+
+		let mut headers = HeaderMap::new();
+		for (name, value) in &exchange.headers {
+			let (Ok(name), Ok(value)) = (HeaderName::try_from(name.as_str()), HeaderValue::try_from(value.as_str())) else { continue };
+			headers.insert(name, value);
+		}
+
+		let body = match exchange.body {
+			Value::String(body) => body.into_bytes(),
+			body => serde_json::to_vec(&body)?,
+		};
+
+		Ok(ApiResponse {
+			status: StatusCode::from_u16(exchange.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+			headers,
+			body,
+		})
+	}
+}
+
+fn parse_body(body: &[u8]) -> Value {
+	serde_json::from_slice(body)
+		.unwrap_or_else(|_| Value::String(String::from_utf8_lossy(body).into_owned()))
+}
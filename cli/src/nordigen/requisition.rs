@@ -2,9 +2,10 @@ use chrono::{DateTime, Local};
 use color_eyre::eyre;
 use serde::{Deserialize, Serialize};
 use crate::nordigen::client_credentials::ClientCredentials;
-use crate::HTTP_CLIENT;
 use crate::nordigen::account::Account;
+use crate::nordigen::api_error::ApiError;
 use crate::nordigen::http_interface;
+use crate::nordigen::http_interface::ApiClient;
 use crate::nordigen::token::Token;
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -17,18 +18,18 @@ pub struct Requisition {
 }
 
 impl Requisition {
-	pub fn new(client_credentials: &ClientCredentials, token: &mut Token, institution_id: &str) -> eyre::Result<Requisition> {
-		let access_token = token.get_access_token(client_credentials)?;
+	pub fn new(client: &ApiClient, client_credentials: &ClientCredentials, token: &mut Token, institution_id: &str) -> Result<Requisition, ApiError> {
+		let access_token = token.get_access_token(client, client_credentials)?;
 
 		let body = http_interface::requisitions::PostRequestBody {
 			redirect: "https://njord.jesperlarsson.me/requisition_return",
 			institution_id,
 		};
 
-		let res = http_interface::requisitions::post(&HTTP_CLIENT, access_token, &body)?;
+		let res = http_interface::requisitions::post(client, access_token, &body)?;
 
 		let accounts = res.accounts.iter()
-			.map(|account_id| { Account::get(client_credentials, token, account_id) })
+			.map(|account_id| { Account::get(client, client_credentials, token, institution_id, account_id) })
 			.flat_map(|res| match res {
 				Ok(account) if account.is_available() => Some(Ok(account)),
 				Ok(_) => None,
@@ -46,13 +47,13 @@ impl Requisition {
 		})
 	}
 
-	pub fn get(client_credentials: &ClientCredentials, token: &mut Token, id: &str) -> eyre::Result<Requisition> {
-		let access_token = token.get_access_token(client_credentials)?;
+	pub fn get(client: &ApiClient, client_credentials: &ClientCredentials, token: &mut Token, id: &str) -> Result<Requisition, ApiError> {
+		let access_token = token.get_access_token(client, client_credentials)?;
 
-		let res = http_interface::requisitions::get(&HTTP_CLIENT, access_token, id)?;
+		let res = http_interface::requisitions::get(client, access_token, id)?;
 
 		let accounts = res.accounts.iter()
-			.map(|account_id| { Account::get(client_credentials, token, account_id) })
+			.map(|account_id| { Account::get(client, client_credentials, token, &res.institution_id, account_id) })
 			.flat_map(|res| match res {
 				Ok(account) if account.is_available() => Some(Ok(account)),
 				Ok(_) => None,
@@ -69,8 +70,8 @@ impl Requisition {
 		})
 	}
 
-	pub fn update(&mut self, client_credentials: &ClientCredentials, token: &mut Token) -> eyre::Result<()> {
-		*self = Requisition::get(client_credentials, token, &self.id)?;
+	pub fn update(&mut self, client: &ApiClient, client_credentials: &ClientCredentials, token: &mut Token) -> Result<(), ApiError> {
+		*self = Requisition::get(client, client_credentials, token, &self.id)?;
 
 		Ok(())
 	}
@@ -1,9 +1,9 @@
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Duration, Local};
-use color_eyre::eyre;
 use crate::nordigen::client_credentials::ClientCredentials;
-use crate::HTTP_CLIENT;
+use crate::nordigen::api_error::ApiError;
 use crate::nordigen::http_interface;
+use crate::nordigen::http_interface::ApiClient;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Token {
@@ -19,10 +19,10 @@ pub struct TokenPart {
 }
 
 impl Token {
-	pub fn new(client_credentials: &ClientCredentials) -> eyre::Result<Token> {
+	pub fn new(client: &ApiClient, client_credentials: &ClientCredentials) -> Result<Token, ApiError> {
 		let start = Local::now();
 		let body = client_credentials.into();
-		let res = http_interface::token::new::post(&HTTP_CLIENT, &body)?;
+		let res = http_interface::token::new::post(client, &body)?;
 
 		Ok(Token {
 			access: TokenPart { secret: res.access, expires_at: start + Duration::seconds(res.access_expires) },
@@ -30,7 +30,7 @@ impl Token {
 		})
 	}
 
-	pub fn get_access_token(&mut self, client_credentials: &ClientCredentials) -> eyre::Result<&str> {
+	pub fn get_access_token(&mut self, client: &ApiClient, client_credentials: &ClientCredentials) -> Result<&str, ApiError> {
 		let time_til_access_expiry = Local::now() - self.access.expires_at;
 		let access_ok = time_til_access_expiry < Duration::seconds(30);
 
@@ -40,16 +40,23 @@ impl Token {
 
 		let start = Local::now();
 		let body = http_interface::token::refresh::PostRequestBody {
-			refresh: self.get_refresh_token(client_credentials)?,
+			refresh: self.get_refresh_token(client, client_credentials)?,
+		};
+		let res = match http_interface::token::refresh::post(client, &body) {
+			Ok(res) => res,
+			Err(ApiError::Unauthorized { .. }) => {
+				*self = Token::new(client, client_credentials)?;
+				return Ok(&self.access.secret);
+			},
+			Err(err) => return Err(err),
 		};
-		let res = http_interface::token::refresh::post(&HTTP_CLIENT, &body)?;
 		self.access.secret = res.access;
 		self.access.expires_at = start + Duration::seconds(res.access_expires);
 
-		return Ok(&self.access.secret);
+		Ok(&self.access.secret)
 	}
 
-	pub fn get_refresh_token(&mut self, client_credentials: &ClientCredentials) -> eyre::Result<&str> {
+	pub fn get_refresh_token(&mut self, client: &ApiClient, client_credentials: &ClientCredentials) -> Result<&str, ApiError> {
 		let time_til_refresh_expiry = Local::now() - self.refresh.expires_at;
 		let refresh_ok = time_til_refresh_expiry < Duration::seconds(30);
 
@@ -57,8 +64,8 @@ impl Token {
 			return Ok(&self.refresh.secret);
 		}
 
-		*self = Token::new(client_credentials)?;
+		*self = Token::new(client, client_credentials)?;
 
-		return Ok(&self.refresh.secret);
+		Ok(&self.refresh.secret)
 	}
 }
@@ -1,39 +1,124 @@
-use chrono::NaiveDate;
-use color_eyre::eyre;
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
 use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
 use crate::nordigen::client_credentials::ClientCredentials;
-use crate::HTTP_CLIENT;
+use crate::nordigen::api_error::ApiError;
 use crate::nordigen::http_interface;
+use crate::nordigen::http_interface::ApiClient;
+use crate::nordigen::http_interface::accounts::transactions::CurrencyExchanges;
+use crate::nordigen::rate_limit::RateLimit;
 use crate::nordigen::token::Token;
 
 #[derive(Debug)]
 pub struct RawTransaction {
-	pub account: String,
 	pub date: NaiveDate,
 	pub currency: String,
 	pub amount: Decimal,
 	pub additional_info: Option<String>,
+	pub remittance_information: Option<String>,
+	pub currency_exchange: Option<CurrencyExchange>,
+	pub counterparty: Option<Counterparty>,
+	pub merchant_category_code: Option<String>,
+	pub balance: Option<Decimal>,
+	pub booked_at: Option<NaiveDateTime>,
 	pub id: String,
 }
 
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Counterparty {
+	pub name: Option<String>,
+	pub iban: Option<String>,
+	pub bban: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CurrencyExchange {
+	pub source_currency: String,
+	pub target_currency: String,
+	pub unit_currency: Option<String>,
+	pub exchange_rate: Decimal,
+}
+
+impl CurrencyExchange {
+	// The exchange rate is the price of one unit currency, which is assumed to be the source currency when not stated.
+	pub fn rate(&self, from: &str, to: &str) -> Option<Decimal> {
+		let is_forward = from == self.source_currency && to == self.target_currency;
+		let is_backward = from == self.target_currency && to == self.source_currency;
+		if !(is_forward || is_backward) || self.exchange_rate.is_zero() { return None; }
+
+		let unit_currency = self.unit_currency.as_deref().unwrap_or(&self.source_currency);
+
+		if unit_currency == from {
+			Some(self.exchange_rate)
+		} else {
+			Some(Decimal::ONE / self.exchange_rate)
+		}
+	}
+}
+
 impl RawTransaction {
-	pub fn list_in_account(client_credentials: &ClientCredentials, token: &mut Token, account_id: &str) -> eyre::Result<Vec<RawTransaction>> {
-		let token = token.get_access_token(&client_credentials)?;
+	// Banks without a booking time are assumed to list the transactions of a day oldest first.
+	pub fn booking_order(&self) -> (NaiveDate, Option<NaiveDateTime>) {
+		(self.date, self.booked_at)
+	}
 
-		let res = http_interface::accounts::transactions::get(&HTTP_CLIENT, token, account_id)?;
+	pub fn list_in_account(client: &ApiClient, client_credentials: &ClientCredentials, token: &mut Token, account_id: &str) -> Result<(Vec<RawTransaction>, Option<RateLimit>), ApiError> {
+		let token = token.get_access_token(client, client_credentials)?;
+
+		let (res, rate_limit) = http_interface::accounts::transactions::get(client, token, account_id)?;
 		let booked_transactions = res.transactions.booked;
 
 		let transactions = booked_transactions.into_iter()
-			.map(|booked_transaction| RawTransaction {
-				account: account_id.to_string(),
-				date: booked_transaction.value_date,
-				currency: booked_transaction.transaction_amount.currency,
-				amount: booked_transaction.transaction_amount.amount,
-				additional_info: booked_transaction.additional_information,
-				id: booked_transaction.transaction_id,
+			.map(|booked_transaction| {
+				// The creditor is on the other side of money going out, the debtor of money coming in.
+				let (name, account) = if booked_transaction.transaction_amount.amount.is_sign_negative() {
+					(booked_transaction.creditor_name, booked_transaction.creditor_account)
+				} else {
+					(booked_transaction.debtor_name, booked_transaction.debtor_account)
+				};
+				let balance = booked_transaction.balance_after_transaction
+					.filter(|balance| balance.balance_amount.currency == booked_transaction.transaction_amount.currency)
+					.map(|balance| balance.balance_amount.amount);
+
+				let counterparty = (name.is_some() || account.is_some()).then(|| Counterparty {
+					name,
+					iban: account.as_ref().and_then(|account| account.iban.clone()),
+					bban: account.and_then(|account| account.bban),
+				});
+
+				RawTransaction {
+					date: booked_transaction.value_date,
+					currency: booked_transaction.transaction_amount.currency,
+					amount: booked_transaction.transaction_amount.amount,
+					additional_info: booked_transaction.additional_information,
+					remittance_information: booked_transaction.remittance_information_unstructured,
+					currency_exchange: booked_transaction.currency_exchange
+						.and_then(|currency_exchanges| match currency_exchanges {
+							CurrencyExchanges::One(currency_exchange) => Some(currency_exchange),
+							CurrencyExchanges::Many(currency_exchanges) => currency_exchanges.into_iter().next(),
+						})
+						.map(|currency_exchange| CurrencyExchange {
+							source_currency: currency_exchange.source_currency,
+							target_currency: currency_exchange.target_currency,
+							unit_currency: currency_exchange.unit_currency,
+							exchange_rate: currency_exchange.exchange_rate,
+						}),
+					counterparty,
+					merchant_category_code: booked_transaction.merchant_category_code,
+					balance,
+					booked_at: booked_transaction.booking_date_time.as_deref().and_then(parse_booking_time),
+					id: booked_transaction.transaction_id,
+				}
 			})
 			.collect();
 
-		Ok(transactions)
+		Ok((transactions, rate_limit))
 	}
 }
+
+// Either with an offset or without, the local time is all that is needed to order the transactions of a day.
+fn parse_booking_time(booking_date_time: &str) -> Option<NaiveDateTime> {
+	DateTime::parse_from_rfc3339(booking_date_time).map(|date_time| date_time.naive_local())
+		.or_else(|_| NaiveDateTime::parse_from_str(booking_date_time, "%Y-%m-%dT%H:%M:%S%.f"))
+		.ok()
+}
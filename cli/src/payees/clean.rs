@@ -0,0 +1,71 @@
+// Wording banks put in front of the merchant on card purchases and payments, compared in lowercase.
+static PREFIXES: &[&str] = &[
+	"kortköp", "kortbetalning", "kortkop", "köp", "betalning", "autogiro", "swish till", "swish från",
+	"card purchase", "card payment", "debit card purchase", "debit card", "contactless", "purchase", "payment to", "payment from", "direct debit",
+	"pos", "visa", "mastercard", "maestro", "apple pay", "google pay",
+	"kartenzahlung", "lastschrift", "sepa", "paiement cb", "achat cb", "cb", "compra", "pago",
+];
+static REFERENCE_WORDS: &[&str] = &["ref", "ref:", "ref.", "reference", "referens", "ocr"];
+
+// Strips the prefixes, dates and reference numbers around the merchant name, and tones down all capitals.
+pub fn clean(text: &str, extra_prefixes: &[String]) -> String {
+	let mut words = text.split_whitespace().collect::<Vec<_>>();
+
+	let prefixes = PREFIXES.iter().map(|prefix| prefix.to_string())
+		.chain(extra_prefixes.iter().map(|prefix| prefix.to_lowercase()))
+		.collect::<Vec<_>>();
+	while let Some(length) = prefix_length(&prefixes, &words) {
+		words.drain(..length);
+	}
+
+	let mut kept = vec![];
+	let mut skip_next = false;
+	for word in words {
+		if skip_next {
+			skip_next = false;
+			continue;
+		}
+		if REFERENCE_WORDS.contains(&word.to_lowercase().as_str()) {
+			skip_next = true;
+			continue;
+		}
+		if is_date_or_reference(word) { continue; }
+		kept.push(word);
+	}
+
+	let payee = kept.join(" ");
+	let payee = payee.trim_matches(|c: char| c.is_whitespace() || matches!(c, ',' | '-' | '/' | '*' | ':'));
+	if payee.chars().any(char::is_lowercase) {
+		payee.to_string()
+	} else {
+		title_case(payee)
+	}
+}
+
+// The number of words taken up by the longest prefix the words start with, as long as something is left after it.
+fn prefix_length(prefixes: &[String], words: &[&str]) -> Option<usize> {
+	prefixes.iter()
+		.map(|prefix| prefix.split_whitespace().collect::<Vec<_>>())
+		.filter(|prefix| prefix.len() < words.len())
+		.filter(|prefix| prefix.iter().zip(words).all(|(prefix, word)| *prefix == word.trim_end_matches(':').to_lowercase()))
+		.map(|prefix| prefix.len())
+		.max()
+}
+
+fn is_date_or_reference(word: &str) -> bool {
+	let digits = word.chars().filter(char::is_ascii_digit).count();
+	let is_numeric = word.chars().all(|c| c.is_ascii_digit() || matches!(c, '-' | '.' | '/' | ':' | '*' | '#' | 'x' | 'X'));
+
+	(is_numeric && digits >= 2) || (digits >= 4 && digits * 2 >= word.chars().count())
+}
+
+fn title_case(text: &str) -> String {
+	let mut previous = ' ';
+	text.chars()
+		.flat_map(|c| {
+			let starts_word = matches!(previous, ' ' | '-' | '/' | '&');
+			previous = c;
+			if starts_word { c.to_uppercase().collect::<Vec<_>>() } else { c.to_lowercase().collect() }
+		})
+		.collect()
+}
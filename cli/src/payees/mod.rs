@@ -0,0 +1,57 @@
+use serde::{Deserialize, Serialize};
+use crate::matcher::{normal_transactions_mut, NormalTransaction, Transaction};
+
+pub mod clean;
+
+// A clean payee name, and the texts that give it away, compared in lowercase against both the bank's text and the cleaned payee.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PayeeAlias {
+	pub payee: String,
+	pub patterns: Vec<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PayeeAliases {
+	pub prefixes: Vec<String>,
+	pub aliases: Vec<PayeeAlias>,
+}
+
+#[derive(Debug, Default)]
+pub struct PayeeNormalizer {
+	aliases: PayeeAliases,
+}
+
+impl PayeeNormalizer {
+	pub fn new(aliases: PayeeAliases) -> PayeeNormalizer {
+		PayeeNormalizer { aliases }
+	}
+
+	// The counterparty name is cleaner than the description when the bank provides it.
+	pub fn payee(&self, transaction: &NormalTransaction) -> Option<String> {
+		let text = transaction.counterparty.as_ref().and_then(|counterparty| counterparty.name.as_ref())
+			.or(transaction.additional_info.as_ref())
+			.or(transaction.remittance_information.as_ref())?;
+		let payee = clean::clean(text, &self.aliases.prefixes);
+
+		let alias = self.aliases.aliases.iter().find(|alias| alias.patterns.iter().any(|pattern| {
+			let pattern = pattern.to_lowercase();
+			text.to_lowercase().contains(&pattern) || payee.to_lowercase().contains(&pattern)
+		}));
+
+		match alias {
+			Some(alias) => Some(alias.payee.clone()),
+			None => Some(payee).filter(|payee| !payee.is_empty()),
+		}
+	}
+
+	pub fn normalize(&self, transaction: &mut NormalTransaction) {
+		if transaction.payee.is_none() {
+			transaction.payee = self.payee(transaction);
+		}
+	}
+
+	pub fn normalize_all(&self, transactions: &mut [Transaction]) {
+		normal_transactions_mut(transactions).for_each(|transaction| self.normalize(transaction));
+	}
+}
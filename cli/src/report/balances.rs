@@ -0,0 +1,149 @@
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::rc::Rc;
+use chrono::NaiveDate;
+use color_eyre::eyre;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use crate::export::account_name;
+use crate::nordigen::account::Account;
+use crate::nordigen::transaction::RawTransaction;
+use crate::report::ReportFormat;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BalanceRow {
+	pub date: NaiveDate,
+	pub account: String,
+	pub currency: String,
+	pub balance: Decimal,
+}
+
+struct AccountBalances {
+	name: String,
+	currency: String,
+	opening: Decimal,
+	end_of_day: BTreeMap<NaiveDate, Decimal>,
+}
+
+// Per account the balance at the end of every day with transactions, and the one before the first of them.
+// Accounts are told apart by ID, as different banks can give accounts the same name, and ordered by name.
+fn balances_per_account(transactions: &[(RawTransaction, Rc<Account>)]) -> BTreeMap<(String, String), AccountBalances> {
+	let mut per_account = BTreeMap::<(String, String), Vec<&RawTransaction>>::new();
+	for (transaction, account) in transactions {
+		if transaction.balance.is_some() {
+			per_account.entry((account_name(account), account.id.clone())).or_default().push(transaction);
+		}
+	}
+
+	per_account.into_iter()
+		.filter_map(|(account, mut transactions)| {
+			transactions.sort_by_key(|transaction| transaction.booking_order());
+			let first = transactions.first()?;
+
+			let balances = AccountBalances {
+				name: account.0.clone(),
+				currency: first.currency.clone(),
+				opening: first.balance? - first.amount,
+				end_of_day: transactions.iter()
+					.filter_map(|transaction| Some((transaction.date, transaction.balance?)))
+					.collect(),
+			};
+			Some((account, balances))
+		})
+		.collect()
+}
+
+// The balance after the latest transaction of each account.
+pub fn latest(transactions: &[(RawTransaction, Rc<Account>)]) -> Vec<BalanceRow> {
+	balances_per_account(transactions).into_values()
+		.filter_map(|balances| {
+			let (date, balance) = balances.end_of_day.last_key_value()?;
+			Some(BalanceRow { date: *date, account: balances.name, currency: balances.currency, balance: *balance })
+		})
+		.collect()
+}
+
+// The balance at the end of every day in the period, which defaults to the days between the first and the last transaction.
+// Days without transactions carry the balance over from the day before.
+pub fn history(transactions: &[(RawTransaction, Rc<Account>)], from: Option<NaiveDate>, until: Option<NaiveDate>) -> Vec<BalanceRow> {
+	let per_account = balances_per_account(transactions);
+
+	let first_date = per_account.values().filter_map(|balances| balances.end_of_day.keys().next()).min();
+	let last_date = per_account.values().filter_map(|balances| balances.end_of_day.keys().next_back()).max();
+	let (Some(from), Some(until)) = (from.or(first_date.copied()), until.or(last_date.copied())) else { return vec![] };
+
+	let mut rows = vec![];
+	for date in from.iter_days().take_while(|date| *date <= until) {
+		for balances in per_account.values() {
+			let balance = balances.end_of_day.range(..=date)
+				.next_back()
+				.map_or(balances.opening, |(_, balance)| *balance);
+			rows.push(BalanceRow { date, account: balances.name.clone(), currency: balances.currency.clone(), balance });
+		}
+	}
+
+	rows
+}
+
+pub fn write(format: ReportFormat, rows: &[BalanceRow], history: bool, mut writer: impl Write) -> eyre::Result<()> {
+	match format {
+		ReportFormat::Table if history => write_history_table(rows, writer)?,
+		ReportFormat::Table => {
+			let width = rows.iter().map(|row| row.account.chars().count()).max().unwrap_or(0);
+			for row in rows {
+				writeln!(writer, "{:width$}  {} {}  on {}", row.account, row.balance, row.currency, row.date)?;
+			}
+		},
+		ReportFormat::Csv => {
+			let mut writer = csv::WriterBuilder::new().from_writer(writer);
+			for row in rows {
+				writer.serialize(row)?;
+			}
+			writer.flush()?;
+		},
+		ReportFormat::Json => {
+			serde_json::to_writer_pretty(&mut writer, rows)?;
+			writeln!(writer)?;
+		},
+	}
+
+	Ok(())
+}
+
+// A row per date with a column per account.
+fn write_history_table(rows: &[BalanceRow], mut writer: impl Write) -> eyre::Result<()> {
+	let mut accounts = rows.iter()
+		.map(|row| format!("{} ({})", row.account, row.currency))
+		.collect::<Vec<_>>();
+	accounts.sort();
+	accounts.dedup();
+
+	let width = rows.iter()
+		.map(|row| row.balance.to_string().len())
+		.chain(accounts.iter().map(|account| account.chars().count()))
+		.max()
+		.unwrap_or(0);
+
+	write!(writer, "{:10}", "Date")?;
+	for account in &accounts {
+		write!(writer, "  {account:>width$}")?;
+	}
+	writeln!(writer)?;
+
+	let mut by_date = BTreeMap::<NaiveDate, BTreeMap<String, Decimal>>::new();
+	for row in rows {
+		by_date.entry(row.date).or_default().insert(format!("{} ({})", row.account, row.currency), row.balance);
+	}
+	for (date, balances) in by_date {
+		write!(writer, "{date}")?;
+		for account in &accounts {
+			match balances.get(account) {
+				Some(balance) => write!(writer, "  {:>width$}", balance.to_string())?,
+				None => write!(writer, "  {:>width$}", "")?,
+			}
+		}
+		writeln!(writer)?;
+	}
+
+	Ok(())
+}
@@ -0,0 +1,156 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::Write;
+use chrono::NaiveDate;
+use color_eyre::eyre;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use crate::matcher::{NormalTransaction, Transaction};
+use crate::report::{with_cents, ReportFormat};
+
+// A budget without a month holds for every month, one with a month ("2023-04") replaces it for that month.
+// It covers the subcategories too, so a budget for "Expenses:Food" counts "Expenses:Food:Groceries".
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Budget {
+	pub category: String,
+	pub amount: Decimal,
+	pub currency: String,
+	#[serde(default)]
+	pub month: Option<String>,
+}
+
+impl Budget {
+	fn covers(&self, category: &str) -> bool {
+		category == self.category || category.strip_prefix(self.category.as_str()).is_some_and(|rest| rest.starts_with(':'))
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BudgetRow {
+	pub month: String,
+	pub category: String,
+	pub currency: String,
+	pub budgeted: Decimal,
+	pub spent: Decimal,
+	pub remaining: Decimal,
+}
+
+impl BudgetRow {
+	pub fn is_overspent(&self) -> bool {
+		self.remaining.is_sign_negative() && !self.remaining.is_zero()
+	}
+}
+
+pub fn parse_month(month: &str) -> Result<String, String> {
+	NaiveDate::parse_from_str(&format!("{month}-01"), "%Y-%m-%d")
+		.map(|date| date.format("%Y-%m").to_string())
+		.map_err(|_| format!("{month} is not a month like 2023-04"))
+}
+
+// Spending per budget in every month with transactions, or only in the given one. Refunds count against the spending.
+// Transfers and splits move money between own accounts, so they are not spending.
+pub fn track(budgets: &[Budget], transactions: &[Transaction], month: Option<&str>) -> Vec<BudgetRow> {
+	let mut spent = BTreeMap::<(String, &str, &str), Decimal>::new();
+	let mut months = BTreeSet::new();
+
+	let mut add = |transaction: &NormalTransaction, amount: Decimal| {
+		let transaction_month = transaction.date.format("%Y-%m").to_string();
+		if month.is_some_and(|month| month != transaction_month) { return; }
+		months.insert(transaction_month.clone());
+
+		let Some(category) = &transaction.category else { return };
+		let Some(budget) = budget_for(budgets, &transaction_month, category, &transaction.currency) else { return };
+		*spent.entry((transaction_month, &budget.category, &budget.currency)).or_default() -= amount;
+	};
+
+	for transaction in transactions {
+		match transaction {
+			Transaction::Normal(transaction) => add(transaction, transaction.amount),
+			Transaction::Correction(transaction) => add(transaction, -transaction.amount),
+			Transaction::Transfer(_) | Transaction::Split(_) => {},
+		}
+	}
+	if let Some(month) = month {
+		months.insert(month.to_string());
+	}
+
+	let mut rows = vec![];
+	for month in months {
+		for budget in budgets_in(budgets, &month) {
+			let spent = spent.get(&(month.clone(), budget.category.as_str(), budget.currency.as_str())).copied().unwrap_or_default();
+			rows.push(BudgetRow {
+				month: month.clone(),
+				category: budget.category.clone(),
+				currency: budget.currency.clone(),
+				budgeted: with_cents(budget.amount),
+				spent: with_cents(spent),
+				remaining: with_cents(budget.amount - spent),
+			});
+		}
+	}
+
+	rows
+}
+
+// The budgets that hold in the month, each category and currency once.
+fn budgets_in<'a>(budgets: &'a [Budget], month: &str) -> Vec<&'a Budget> {
+	let mut in_month = BTreeMap::<(&str, &str), &Budget>::new();
+	for budget in budgets {
+		match budget.month.as_deref() {
+			Some(budget_month) if budget_month == month => { in_month.insert((&budget.category, &budget.currency), budget); },
+			None => { in_month.entry((&budget.category, &budget.currency)).or_insert(budget); },
+			Some(_) => {},
+		}
+	}
+	in_month.into_values().collect()
+}
+
+// The most specific budget covering the category.
+fn budget_for<'a>(budgets: &'a [Budget], month: &str, category: &str, currency: &str) -> Option<&'a Budget> {
+	budgets_in(budgets, month).into_iter()
+		.filter(|budget| budget.currency == currency && budget.covers(category))
+		.max_by_key(|budget| budget.category.len())
+}
+
+pub fn write(format: ReportFormat, rows: &[BudgetRow], mut writer: impl Write) -> eyre::Result<()> {
+	match format {
+		ReportFormat::Table => {
+			let category_width = rows.iter().map(|row| row.category.chars().count()).max().unwrap_or(0).max("Category".len());
+			let amount_width = rows.iter()
+				.flat_map(|row| [row.budgeted, row.spent, row.remaining])
+				.map(|amount| amount.to_string().len())
+				.max()
+				.unwrap_or(0)
+				.max("Remaining".len());
+
+			let mut month = None;
+			for row in rows {
+				if month != Some(&row.month) {
+					if month.is_some() {
+						writeln!(writer)?;
+					}
+					writeln!(writer, "{}", row.month)?;
+					writeln!(writer, "  {:category_width$}  {:>amount_width$}  {:>amount_width$}  {:>amount_width$}", "Category", "Budgeted", "Spent", "Remaining")?;
+					month = Some(&row.month);
+				}
+				write!(writer, "  {:category_width$}  {:>amount_width$}  {:>amount_width$}  {:>amount_width$}  {}", row.category, row.budgeted, row.spent, row.remaining, row.currency)?;
+				if row.is_overspent() {
+					write!(writer, "  overspent by {} {}", -row.remaining, row.currency)?;
+				}
+				writeln!(writer)?;
+			}
+		},
+		ReportFormat::Csv => {
+			let mut writer = csv::WriterBuilder::new().from_writer(writer);
+			for row in rows {
+				writer.serialize(row)?;
+			}
+			writer.flush()?;
+		},
+		ReportFormat::Json => {
+			serde_json::to_writer_pretty(&mut writer, rows)?;
+			writeln!(writer)?;
+		},
+	}
+
+	Ok(())
+}
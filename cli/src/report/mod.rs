@@ -0,0 +1,120 @@
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::io::Write;
+use clap::ValueEnum;
+use color_eyre::eyre;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use crate::export::account_name;
+use crate::matcher::{NormalTransaction, Transaction};
+
+pub mod balances;
+pub mod budget;
+pub mod net_worth;
+pub mod recurring;
+pub mod table;
+
+static UNCATEGORIZED: &str = "Uncategorized";
+static TOTAL: &str = "Total";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ReportFormat {
+	Table,
+	Csv,
+	Json,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Group {
+	Account,
+	Category,
+	Total,
+}
+
+impl Display for Group {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Group::Account => write!(f, "Account"),
+			Group::Category => write!(f, "Category"),
+			Group::Total => write!(f, "Total"),
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ReportRow {
+	pub month: String,
+	pub group: Group,
+	pub name: String,
+	pub currency: String,
+	pub income: Decimal,
+	pub expenses: Decimal,
+	pub net: Decimal,
+}
+
+// Income and expenses per month, once per account, once per category and once in total.
+// Transfers and splits move money between own accounts, so they are neither.
+pub fn aggregate(transactions: &[Transaction]) -> Vec<ReportRow> {
+	let mut sums = BTreeMap::<(String, Group, String, String), (Decimal, Decimal)>::new();
+
+	let mut add = |transaction: &NormalTransaction, amount: Decimal| {
+		let month = transaction.date.format("%Y-%m").to_string();
+		let category = transaction.category.clone().unwrap_or(UNCATEGORIZED.to_string());
+
+		for (group, name) in [(Group::Account, account_name(&transaction.account)), (Group::Category, category), (Group::Total, TOTAL.to_string())] {
+			let (income, expenses) = sums.entry((month.clone(), group, name, transaction.currency.clone())).or_default();
+			if amount.is_sign_negative() {
+				*expenses -= amount;
+			} else {
+				*income += amount;
+			}
+		}
+	};
+
+	for transaction in transactions {
+		match transaction {
+			Transaction::Normal(transaction) => add(transaction, transaction.amount),
+			Transaction::Correction(transaction) => add(transaction, -transaction.amount),
+			Transaction::Transfer(_) | Transaction::Split(_) => {},
+		}
+	}
+
+	sums.into_iter()
+		.map(|((month, group, name, currency), (income, expenses))| ReportRow {
+			month,
+			group,
+			name,
+			currency,
+			income: with_cents(income),
+			expenses: with_cents(expenses),
+			net: with_cents(income - expenses),
+		})
+		.collect()
+}
+
+pub fn with_cents(mut amount: Decimal) -> Decimal {
+	if amount.scale() < 2 {
+		amount.rescale(2);
+	}
+	amount
+}
+
+pub fn write(format: ReportFormat, rows: &[ReportRow], mut writer: impl Write) -> eyre::Result<()> {
+	match format {
+		ReportFormat::Table => table::write(rows, writer)?,
+		ReportFormat::Csv => {
+			let mut writer = csv::WriterBuilder::new().from_writer(writer);
+			for row in rows {
+				writer.serialize(row)?;
+			}
+			writer.flush()?;
+		},
+		ReportFormat::Json => {
+			serde_json::to_writer_pretty(&mut writer, rows)?;
+			writeln!(writer)?;
+		},
+	}
+
+	Ok(())
+}
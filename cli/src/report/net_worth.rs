@@ -0,0 +1,173 @@
+use std::io::Write;
+use chrono::NaiveDate;
+use color_eyre::eyre;
+use color_eyre::eyre::eyre;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use crate::export::account_name;
+use crate::matcher::exchange::ExchangeRate;
+use crate::nordigen::AccountBalances;
+use crate::report::ReportFormat;
+
+// Kept apart from the matcher's exchange rates, as these should be kept up to date for the totals to mean anything.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NetWorthRates {
+	pub base_currency: String,
+	pub rates: Vec<ExchangeRate>,
+}
+
+impl Default for NetWorthRates {
+	fn default() -> Self {
+		NetWorthRates {
+			base_currency: "EUR".into(),
+			rates: vec![],
+		}
+	}
+}
+
+impl NetWorthRates {
+	pub fn convert(&self, amount: Decimal, currency: &str) -> Option<Decimal> {
+		if currency == self.base_currency {
+			return Some(amount);
+		}
+
+		self.rates.iter()
+			.find_map(|exchange_rate| exchange_rate.rate(currency, &self.base_currency))
+			.map(|rate| (amount * rate).round_dp(2))
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccountValue {
+	pub account: String,
+	pub balance: Decimal,
+	pub currency: String,
+	pub value: Decimal,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Snapshot {
+	pub date: NaiveDate,
+	pub base_currency: String,
+	pub accounts: Vec<AccountValue>,
+	pub total: Decimal,
+}
+
+impl Snapshot {
+	pub fn take(date: NaiveDate, balances: &[AccountBalances], rates: &NetWorthRates) -> eyre::Result<Snapshot> {
+		let mut accounts = vec![];
+		for AccountBalances { account, amounts } in balances {
+			for (balance, currency) in amounts {
+				let value = rates.convert(*balance, currency)
+					.ok_or_else(|| eyre!("There is no exchange rate from {currency} to {} in rates.ron, needed for {account}", rates.base_currency))?;
+				accounts.push(AccountValue { account: account_name(account), balance: *balance, currency: currency.clone(), value });
+			}
+		}
+		accounts.sort_by(|a, b| (&a.account, &a.currency).cmp(&(&b.account, &b.currency)));
+
+		Ok(Snapshot {
+			date,
+			base_currency: rates.base_currency.clone(),
+			total: accounts.iter().map(|account| account.value).sum(),
+			accounts,
+		})
+	}
+}
+
+// A snapshot per day, the latest one taken on a day replaces the earlier ones.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct NetWorthHistory {
+	pub snapshots: Vec<Snapshot>,
+}
+
+impl NetWorthHistory {
+	pub fn add(&mut self, snapshot: Snapshot) {
+		self.snapshots.retain(|existing| existing.date != snapshot.date);
+		self.snapshots.push(snapshot);
+		self.snapshots.sort_by_key(|snapshot| snapshot.date);
+	}
+}
+
+#[derive(Serialize)]
+struct SnapshotRow<'a> {
+	date: NaiveDate,
+	account: &'a str,
+	balance: Decimal,
+	currency: &'a str,
+	value: Decimal,
+	base_currency: &'a str,
+}
+
+#[derive(Serialize)]
+struct TotalRow<'a> {
+	date: NaiveDate,
+	total: Decimal,
+	base_currency: &'a str,
+}
+
+pub fn write_snapshot(format: ReportFormat, snapshot: &Snapshot, mut writer: impl Write) -> eyre::Result<()> {
+	match format {
+		ReportFormat::Table => {
+			let width = snapshot.accounts.iter().map(|account| account.account.chars().count()).max().unwrap_or(0).max("Total".len());
+			let balance_width = snapshot.accounts.iter().map(|account| account.balance.to_string().len() + 1 + account.currency.len()).max().unwrap_or(0);
+			let value_width = snapshot.accounts.iter().map(|account| account.value).chain([snapshot.total]).map(|value| value.to_string().len()).max().unwrap_or(0);
+
+			writeln!(writer, "Net worth on {}", snapshot.date)?;
+			for account in &snapshot.accounts {
+				let balance = format!("{} {}", account.balance, account.currency);
+				writeln!(writer, "  {:width$}  {balance:>balance_width$}  {:>value_width$} {}", account.account, account.value, snapshot.base_currency)?;
+			}
+			writeln!(writer, "  {:width$}  {:balance_width$}  {:>value_width$} {}", "Total", "", snapshot.total, snapshot.base_currency)?;
+		},
+		ReportFormat::Csv => {
+			let mut writer = csv::WriterBuilder::new().from_writer(writer);
+			for account in &snapshot.accounts {
+				writer.serialize(SnapshotRow {
+					date: snapshot.date,
+					account: &account.account,
+					balance: account.balance,
+					currency: &account.currency,
+					value: account.value,
+					base_currency: &snapshot.base_currency,
+				})?;
+			}
+			writer.flush()?;
+		},
+		ReportFormat::Json => {
+			serde_json::to_writer_pretty(&mut writer, snapshot)?;
+			writeln!(writer)?;
+		},
+	}
+
+	Ok(())
+}
+
+// The total of every stored snapshot, for charting.
+pub fn write_history(format: ReportFormat, history: &NetWorthHistory, mut writer: impl Write) -> eyre::Result<()> {
+	let rows = history.snapshots.iter()
+		.map(|snapshot| TotalRow { date: snapshot.date, total: snapshot.total, base_currency: &snapshot.base_currency })
+		.collect::<Vec<_>>();
+
+	match format {
+		ReportFormat::Table => {
+			let width = rows.iter().map(|row| row.total.to_string().len()).max().unwrap_or(0);
+			for row in &rows {
+				writeln!(writer, "{}  {:>width$} {}", row.date, row.total, row.base_currency)?;
+			}
+		},
+		ReportFormat::Csv => {
+			let mut writer = csv::WriterBuilder::new().from_writer(writer);
+			for row in &rows {
+				writer.serialize(row)?;
+			}
+			writer.flush()?;
+		},
+		ReportFormat::Json => {
+			serde_json::to_writer_pretty(&mut writer, &history.snapshots)?;
+			writeln!(writer)?;
+		},
+	}
+
+	Ok(())
+}
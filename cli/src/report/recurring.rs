@@ -0,0 +1,192 @@
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::io::Write;
+use chrono::{Months, NaiveDate};
+use color_eyre::eyre;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use crate::export::account_name;
+use crate::matcher::{NormalTransaction, Transaction};
+use crate::report::ReportFormat;
+
+// Amounts within this of each other are the same price, a bigger step is a price change.
+const AMOUNT_TOLERANCE: Decimal = Decimal::from_parts(25, 0, 0, false, 2);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Cadence {
+	Weekly,
+	Monthly,
+	Yearly,
+}
+
+impl Cadence {
+	fn after(&self, date: NaiveDate, occurrences: u32) -> NaiveDate {
+		match self {
+			Cadence::Weekly => date + chrono::Duration::weeks(occurrences.into()),
+			Cadence::Monthly => date.checked_add_months(Months::new(occurrences)).unwrap_or(date),
+			Cadence::Yearly => date.checked_add_months(Months::new(12 * occurrences)).unwrap_or(date),
+		}
+	}
+
+	// How many days a charge may come early or late, banks book on the next working day and months differ in length.
+	fn slack_days(&self) -> i64 {
+		match self {
+			Cadence::Weekly => 1,
+			Cadence::Monthly => 4,
+			Cadence::Yearly => 10,
+		}
+	}
+
+	fn minimum_occurrences(&self) -> usize {
+		match self {
+			Cadence::Weekly | Cadence::Monthly => 3,
+			Cadence::Yearly => 2,
+		}
+	}
+
+	// The number of periods from one date to the other, if it lands close enough to a whole one.
+	fn periods_between(&self, from: NaiveDate, to: NaiveDate) -> Option<u32> {
+		(1..=12).find(|periods| (self.after(from, *periods) - to).num_days().abs() <= self.slack_days())
+	}
+}
+
+impl Display for Cadence {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Cadence::Weekly => write!(f, "weekly"),
+			Cadence::Monthly => write!(f, "monthly"),
+			Cadence::Yearly => write!(f, "yearly"),
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RecurringRow {
+	pub account: String,
+	pub payee: String,
+	pub cadence: Cadence,
+	pub occurrences: usize,
+	pub last_date: NaiveDate,
+	pub amount: Decimal,
+	pub currency: String,
+	pub next_date: NaiveDate,
+	// The amount before the latest one, when the latest one differs.
+	pub previous_amount: Option<Decimal>,
+	// Expected charges that never came, between the first one and today.
+	pub missed: u32,
+}
+
+// Groups the transactions per account, payee and direction, and keeps the groups that come at a steady cadence at a steady price.
+// Only the given transactions are looked at, usually the 90 days the bank returns, so a yearly charge needs a bank that returns more.
+pub fn detect(transactions: &[Transaction], today: NaiveDate) -> Vec<RecurringRow> {
+	let mut groups = BTreeMap::<(String, String, String, bool), Vec<&NormalTransaction>>::new();
+	for transaction in transactions {
+		let Transaction::Normal(transaction) = transaction else { continue };
+		let Some(payee) = payee(transaction) else { continue };
+		groups.entry((account_name(&transaction.account), payee.to_lowercase(), transaction.currency.clone(), transaction.amount.is_sign_negative()))
+			.or_default()
+			.push(transaction);
+	}
+
+	groups.into_values()
+		.filter_map(|mut transactions| {
+			transactions.sort_by_key(|transaction| transaction.date);
+			recurring(&transactions, today)
+		})
+		.collect()
+}
+
+fn payee(transaction: &NormalTransaction) -> Option<String> {
+	transaction.payee.clone()
+		.or_else(|| transaction.counterparty.as_ref().and_then(|counterparty| counterparty.name.clone()))
+		.or_else(|| transaction.additional_info.clone())
+		.filter(|payee| !payee.trim().is_empty())
+}
+
+fn recurring(transactions: &[&NormalTransaction], today: NaiveDate) -> Option<RecurringRow> {
+	let last = transactions.last()?;
+
+	// Every price is charged at least twice before it changes, amounts that keep jumping around are not one charge.
+	// The latest price may have been charged just once so far.
+	let mut price = transactions[0].amount;
+	let mut runs = vec![0];
+	for transaction in transactions {
+		if (transaction.amount - price).abs() > price.abs() * AMOUNT_TOLERANCE {
+			price = transaction.amount;
+			runs.push(0);
+		}
+		if let Some(run) = runs.last_mut() {
+			*run += 1;
+		}
+	}
+	if runs[..runs.len() - 1].iter().any(|run| *run < 2) {
+		return None;
+	}
+
+	let (cadence, periods) = [Cadence::Weekly, Cadence::Monthly, Cadence::Yearly].into_iter()
+		.filter(|cadence| transactions.len() >= cadence.minimum_occurrences())
+		.find_map(|cadence| {
+			// Mostly single periods, a gap of a few is a missed charge rather than a slower cadence.
+			let periods = transactions.windows(2)
+				.map(|pair| cadence.periods_between(pair[0].date, pair[1].date))
+				.collect::<Option<Vec<_>>>()?;
+			let single = periods.iter().filter(|periods| **periods == 1).count();
+			(single * 2 > periods.len()).then_some((cadence, periods))
+		})?;
+
+	let mut missed = periods.iter().map(|periods| periods - 1).sum::<u32>();
+	let mut next_date = cadence.after(last.date, 1);
+	while (today - next_date).num_days() > cadence.slack_days() {
+		missed += 1;
+		next_date = cadence.after(next_date, 1);
+	}
+
+	let previous = transactions[transactions.len() - 2];
+	Some(RecurringRow {
+		account: account_name(&last.account),
+		payee: payee(last).unwrap_or_default(),
+		cadence,
+		occurrences: transactions.len(),
+		last_date: last.date,
+		amount: last.amount,
+		currency: last.currency.clone(),
+		next_date,
+		previous_amount: (previous.amount != last.amount).then_some(previous.amount),
+		missed,
+	})
+}
+
+pub fn write(format: ReportFormat, rows: &[RecurringRow], mut writer: impl Write) -> eyre::Result<()> {
+	match format {
+		ReportFormat::Table => {
+			let account_width = rows.iter().map(|row| row.account.chars().count()).max().unwrap_or(0);
+			let payee_width = rows.iter().map(|row| row.payee.chars().count()).max().unwrap_or(0);
+			let amount_width = rows.iter().map(|row| row.amount.to_string().len()).max().unwrap_or(0);
+
+			for row in rows {
+				write!(writer, "{:account_width$}  {:payee_width$}  {:7}  {:>amount_width$} {}  next {}", row.account, row.payee, row.cadence.to_string(), row.amount, row.currency, row.next_date)?;
+				if let Some(previous_amount) = row.previous_amount {
+					write!(writer, "  changed from {previous_amount}")?;
+				}
+				if row.missed > 0 {
+					write!(writer, "  missed {}", row.missed)?;
+				}
+				writeln!(writer)?;
+			}
+		},
+		ReportFormat::Csv => {
+			let mut writer = csv::WriterBuilder::new().from_writer(writer);
+			for row in rows {
+				writer.serialize(row)?;
+			}
+			writer.flush()?;
+		},
+		ReportFormat::Json => {
+			serde_json::to_writer_pretty(&mut writer, rows)?;
+			writeln!(writer)?;
+		},
+	}
+
+	Ok(())
+}
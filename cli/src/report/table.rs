@@ -0,0 +1,35 @@
+use std::io::Write;
+use color_eyre::eyre;
+use crate::report::{Group, ReportRow};
+
+// A table per month with the accounts, then the categories, then the total for each currency.
+pub fn write(rows: &[ReportRow], mut writer: impl Write) -> eyre::Result<()> {
+	let name_width = rows.iter().map(|row| row.name.chars().count()).max().unwrap_or(0).max("Category".len());
+	let amount_width = rows.iter()
+		.flat_map(|row| [row.income, row.expenses, row.net])
+		.map(|amount| amount.to_string().len())
+		.max()
+		.unwrap_or(0)
+		.max("Expenses".len());
+
+	let mut month = None;
+	let mut group = None;
+	for row in rows {
+		if month != Some(&row.month) {
+			if month.is_some() {
+				writeln!(writer)?;
+			}
+			writeln!(writer, "{}", row.month)?;
+			month = Some(&row.month);
+			group = None;
+		}
+		if group != Some(row.group) {
+			writeln!(writer, "  {:name_width$}  {:>amount_width$}  {:>amount_width$}  {:>amount_width$}", row.group.to_string(), "Income", "Expenses", "Net")?;
+			group = Some(row.group);
+		}
+		let name = if row.group == Group::Total { "" } else { row.name.as_str() };
+		writeln!(writer, "  {name:name_width$}  {:>amount_width$}  {:>amount_width$}  {:>amount_width$}  {}", row.income, row.expenses, row.net, row.currency)?;
+	}
+
+	Ok(())
+}
@@ -0,0 +1,261 @@
+use chrono::NaiveDate;
+use ratatui::widgets::TableState;
+use rust_decimal::Decimal;
+use crate::matcher::decisions::Decisions;
+use crate::matcher::{pair, NormalTransaction, Transaction};
+use crate::tui::filter::Filter;
+
+pub enum Mode {
+	Browse,
+	Filter(String),
+	EditDescription(String),
+	EditCategory(String),
+}
+
+pub struct RowSummary {
+	pub date: NaiveDate,
+	pub accounts: String,
+	pub amount: Decimal,
+	pub currency: String,
+	pub description: String,
+	pub category: String,
+	pub kind: &'static str,
+}
+
+impl RowSummary {
+	pub fn new(transaction: &Transaction) -> RowSummary {
+		match transaction {
+			Transaction::Normal(transaction) => RowSummary {
+				date: transaction.date,
+				accounts: transaction.account.to_string(),
+				amount: transaction.amount,
+				currency: transaction.currency.clone(),
+				description: transaction.additional_info.clone().unwrap_or_default(),
+				category: transaction.category.clone().unwrap_or_default(),
+				kind: if transaction.previously_exported { "earlier" } else { "" },
+			},
+			Transaction::Transfer(transaction) => RowSummary {
+				date: transaction.date,
+				accounts: format!("{} → {}", transaction.from, transaction.to),
+				amount: transaction.amount,
+				currency: transaction.currency.clone(),
+				description: [&transaction.from_additional_info, &transaction.to_additional_info].into_iter()
+					.flatten()
+					.cloned()
+					.collect::<Vec<_>>()
+					.join(" / "),
+				category: String::new(),
+				kind: "transfer",
+			},
+			Transaction::Split(transaction) => RowSummary {
+				date: transaction.date,
+				accounts: format!("{} ⇄ {} accounts", transaction.account, transaction.legs.len()),
+				amount: transaction.amount,
+				currency: transaction.currency.clone(),
+				description: transaction.additional_info.clone().unwrap_or_default(),
+				category: String::new(),
+				kind: "split",
+			},
+			Transaction::Correction(transaction) => RowSummary {
+				date: transaction.date,
+				accounts: transaction.account.to_string(),
+				amount: -transaction.amount,
+				currency: transaction.currency.clone(),
+				description: transaction.additional_info.clone().unwrap_or_default(),
+				category: String::new(),
+				kind: "reversal",
+			},
+		}
+	}
+}
+
+pub struct App<'a> {
+	pub transactions: Vec<Transaction>,
+	pub visible: Vec<usize>,
+	pub table_state: TableState,
+	pub marked: Option<usize>,
+	pub filter: Filter,
+	pub mode: Mode,
+	pub status: String,
+	decisions: &'a mut Decisions,
+}
+
+impl<'a> App<'a> {
+	pub fn new(transactions: Vec<Transaction>, decisions: &'a mut Decisions) -> App<'a> {
+		let mut app = App {
+			transactions,
+			visible: vec![],
+			table_state: TableState::default(),
+			marked: None,
+			filter: Filter::default(),
+			mode: Mode::Browse,
+			status: String::new(),
+			decisions,
+		};
+		app.refresh();
+		app
+	}
+
+	pub fn refresh(&mut self) {
+		self.visible = self.transactions.iter()
+			.enumerate()
+			.filter(|(_, transaction)| self.filter.matches(&RowSummary::new(transaction)))
+			.map(|(index, _)| index)
+			.collect();
+
+		let selected = self.table_state.selected().unwrap_or(0).min(self.visible.len().saturating_sub(1));
+		self.table_state.select((!self.visible.is_empty()).then_some(selected));
+	}
+
+	pub fn current(&self) -> Option<usize> {
+		self.table_state.selected().and_then(|selected| self.visible.get(selected).copied())
+	}
+
+	pub fn move_by(&mut self, offset: isize) {
+		if self.visible.is_empty() { return; }
+
+		let selected = self.table_state.selected().unwrap_or(0) as isize + offset;
+		self.table_state.select(Some(selected.clamp(0, self.visible.len() as isize - 1) as usize));
+	}
+
+	pub fn set_filter(&mut self, query: &str) {
+		match Filter::parse(query) {
+			Ok(filter) => {
+				self.filter = filter;
+				self.refresh();
+				self.status = format!("{} of {} rows shown", self.visible.len(), self.transactions.len());
+			},
+			Err(err) => self.status = err,
+		}
+	}
+
+	pub fn toggle_mark(&mut self) {
+		let Some(current) = self.current() else { return };
+		if !matches!(self.transactions[current], Transaction::Normal(_)) {
+			self.status = "Only unmatched transactions can be paired".into();
+			return;
+		}
+
+		self.marked = if self.marked == Some(current) { None } else { Some(current) };
+		self.status = match self.marked {
+			Some(_) => "Marked, move to the other half and press p to pair".into(),
+			None => String::new(),
+		};
+	}
+
+	pub fn pair_marked(&mut self) {
+		let (Some(marked), Some(current)) = (self.marked, self.current()) else {
+			self.status = "Mark a transaction with space first".into();
+			return;
+		};
+
+		let (Transaction::Normal(a), Transaction::Normal(b)) = (&self.transactions[marked], &self.transactions[current]) else {
+			self.status = "Only unmatched transactions can be paired".into();
+			return;
+		};
+		if let Some(problem) = pairing_problem(a, b) {
+			self.status = problem.into();
+			return;
+		}
+		self.decisions.record(a, &[b], Some(b));
+
+		let (first, second) = (marked.min(current), marked.max(current));
+		let Transaction::Normal(b) = self.transactions.remove(second) else { unreachable!() };
+		let Transaction::Normal(a) = self.transactions.remove(first) else { unreachable!() };
+
+		let correction = [&a, &b].into_iter()
+			.find(|transaction| transaction.previously_exported)
+			.map(|transaction| Transaction::Correction(transaction.clone()));
+		self.transactions.insert(first, Transaction::Transfer(pair(a, b)));
+		if let Some(correction) = correction {
+			self.transactions.insert(first, correction);
+		}
+
+		self.marked = None;
+		self.status = "Paired".into();
+		self.refresh();
+	}
+
+	pub fn unpair(&mut self) {
+		let Some(current) = self.current() else { return };
+
+		let sources = match &self.transactions[current] {
+			Transaction::Transfer(transfer) => {
+				if let [a, b] = transfer.sources.as_slice() {
+					self.decisions.record(a, &[b], None);
+				}
+				transfer.sources.clone()
+			},
+			Transaction::Split(split) => {
+				let mut sources = vec![split.source.as_ref().clone()];
+				sources.extend(split.legs.iter().cloned());
+				sources
+			},
+			_ => {
+				self.status = "Only transfers can be unpaired".into();
+				return;
+			},
+		};
+
+		self.transactions.remove(current);
+		let mut index = current;
+		for source in sources {
+			if source.previously_exported {
+				let correction = self.transactions.iter()
+					.position(|transaction| matches!(transaction, Transaction::Correction(correction) if correction.id == source.id));
+				if let Some(correction) = correction {
+					self.transactions.remove(correction);
+					if correction < index { index -= 1; }
+				}
+				self.transactions.push(Transaction::Normal(source));
+			} else {
+				self.transactions.insert(index, Transaction::Normal(source));
+				index += 1;
+			}
+		}
+
+		self.marked = None;
+		self.status = "Unpaired".into();
+		self.refresh();
+	}
+
+	pub fn start_edit(&mut self, category: bool) {
+		let Some(Transaction::Normal(transaction)) = self.current().map(|current| &self.transactions[current]) else {
+			self.status = "Only unmatched transactions can be edited".into();
+			return;
+		};
+
+		self.mode = if category {
+			Mode::EditCategory(transaction.category.clone().unwrap_or_default())
+		} else {
+			Mode::EditDescription(transaction.additional_info.clone().unwrap_or_default())
+		};
+	}
+
+	pub fn finish_edit(&mut self) {
+		let mode = std::mem::replace(&mut self.mode, Mode::Browse);
+		let Some(current) = self.current() else { return };
+		let Transaction::Normal(transaction) = &mut self.transactions[current] else { return };
+
+		match mode {
+			Mode::EditDescription(description) => transaction.additional_info = Some(description).filter(|text| !text.is_empty()),
+			Mode::EditCategory(category) => transaction.category = Some(category.trim().to_string()).filter(|text| !text.is_empty()),
+			Mode::Filter(query) => self.set_filter(&query),
+			Mode::Browse => {},
+		}
+	}
+}
+
+fn pairing_problem(a: &NormalTransaction, b: &NormalTransaction) -> Option<&'static str> {
+	if a.id == b.id {
+		Some("A transaction can't be paired with itself")
+	} else if a.account.id == b.account.id {
+		Some("Both transactions are in the same account")
+	} else if a.amount.is_sign_negative() == b.amount.is_sign_negative() {
+		Some("One transaction has to be going out and the other coming in")
+	} else if a.previously_exported && b.previously_exported {
+		Some("Both transactions were exported in an earlier run")
+	} else {
+		None
+	}
+}
@@ -0,0 +1,50 @@
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use crate::tui::app::RowSummary;
+
+// Space separated terms, all of which have to match:
+// account:<text>, from:<date>, to:<date>, min:<amount>, max:<amount>, or text anywhere in the description.
+#[derive(Debug, Default)]
+pub struct Filter {
+	pub query: String,
+	accounts: Vec<String>,
+	from: Option<NaiveDate>,
+	to: Option<NaiveDate>,
+	min: Option<Decimal>,
+	max: Option<Decimal>,
+	text: Vec<String>,
+}
+
+impl Filter {
+	pub fn parse(query: &str) -> Result<Filter, String> {
+		let mut filter = Filter { query: query.trim().to_string(), ..Filter::default() };
+
+		for term in query.split_whitespace() {
+			let (key, value) = term.split_once(':').unwrap_or(("", term));
+			let invalid = |kind: &str| format!("{value} is not a valid {kind}");
+
+			match key {
+				"account" => filter.accounts.push(value.to_lowercase()),
+				"from" => filter.from = Some(value.parse().map_err(|_| invalid("date"))?),
+				"to" => filter.to = Some(value.parse().map_err(|_| invalid("date"))?),
+				"min" => filter.min = Some(value.parse().map_err(|_| invalid("amount"))?),
+				"max" => filter.max = Some(value.parse().map_err(|_| invalid("amount"))?),
+				_ => filter.text.push(term.to_lowercase()),
+			}
+		}
+
+		Ok(filter)
+	}
+
+	pub fn matches(&self, row: &RowSummary) -> bool {
+		let accounts = row.accounts.to_lowercase();
+		let description = row.description.to_lowercase();
+
+		self.accounts.iter().all(|account| accounts.contains(account))
+			&& self.from.is_none_or(|from| row.date >= from)
+			&& self.to.is_none_or(|to| row.date <= to)
+			&& self.min.is_none_or(|min| row.amount.abs() >= min)
+			&& self.max.is_none_or(|max| row.amount.abs() <= max)
+			&& self.text.iter().all(|text| description.contains(text))
+	}
+}
@@ -0,0 +1,115 @@
+use std::io::stderr;
+use std::panic;
+use std::panic::PanicHookInfo;
+use std::sync::Arc;
+use std::thread;
+use color_eyre::eyre;
+use color_eyre::eyre::eyre;
+use ratatui::backend::CrosstermBackend;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::crossterm::execute;
+use ratatui::crossterm::terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen};
+use ratatui::Terminal;
+use crate::matcher::decisions::Decisions;
+use crate::matcher::Transaction;
+use crate::tui::app::{App, Mode};
+
+pub mod app;
+mod filter;
+mod ui;
+
+// Drawn on stderr, so the export on stdout can still be redirected to a file.
+// Ctrl-C aborts the review with an error, so nothing half reviewed is exported or remembered.
+pub fn review(transactions: Vec<Transaction>, decisions: &mut Decisions) -> eyre::Result<Vec<Transaction>> {
+	let terminal_guard = TerminalGuard::enter()?;
+
+	let mut app = App::new(transactions, decisions);
+	let result = run(&mut app);
+
+	drop(terminal_guard);
+	result.map(|_| app.transactions)
+}
+
+// Puts the terminal back however the review ends. A panic restores it before its message is printed,
+// as the message would otherwise go to the alternate screen and the shell would be left in raw mode.
+// The hook that was there before is put back afterwards, so a later review doesn't wrap it once more.
+struct TerminalGuard {
+	previous_hook: Arc<PanicHook>,
+}
+
+type PanicHook = Box<dyn Fn(&PanicHookInfo<'_>) + Send + Sync + 'static>;
+
+impl TerminalGuard {
+	fn enter() -> eyre::Result<TerminalGuard> {
+		let previous_hook = Arc::new(panic::take_hook());
+		let panic_hook = previous_hook.clone();
+		panic::set_hook(Box::new(move |info| {
+			restore_terminal();
+			panic_hook(info);
+		}));
+
+		let guard = TerminalGuard { previous_hook };
+		enable_raw_mode()?;
+		execute!(stderr(), EnterAlternateScreen)?;
+		Ok(guard)
+	}
+}
+
+impl Drop for TerminalGuard {
+	fn drop(&mut self) {
+		restore_terminal();
+
+		// The hook can't be changed while a panic is unwinding, the process is on its way out then anyway.
+		if !thread::panicking() {
+			let previous_hook = self.previous_hook.clone();
+			panic::set_hook(Box::new(move |info| previous_hook(info)));
+		}
+	}
+}
+
+fn restore_terminal() {
+	let _ = disable_raw_mode();
+	let _ = execute!(stderr(), LeaveAlternateScreen);
+}
+
+fn run(app: &mut App) -> eyre::Result<()> {
+	let mut terminal = Terminal::new(CrosstermBackend::new(stderr()))?;
+
+	loop {
+		terminal.draw(|frame| ui::draw(frame, app))?;
+
+		let Event::Key(key) = event::read()? else { continue };
+		if key.kind != KeyEventKind::Press { continue; }
+
+		if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+			return Err(eyre!("Review aborted, nothing is exported"));
+		}
+
+		match &mut app.mode {
+			Mode::Browse => match key.code {
+				KeyCode::Char('q') => return Ok(()),
+				KeyCode::Up | KeyCode::Char('k') => app.move_by(-1),
+				KeyCode::Down | KeyCode::Char('j') => app.move_by(1),
+				KeyCode::PageUp => app.move_by(-20),
+				KeyCode::PageDown => app.move_by(20),
+				KeyCode::Home => app.move_by(isize::MIN / 2),
+				KeyCode::End => app.move_by(isize::MAX / 2),
+				KeyCode::Char(' ') => app.toggle_mark(),
+				KeyCode::Char('p') => app.pair_marked(),
+				KeyCode::Char('u') => app.unpair(),
+				KeyCode::Char('d') => app.start_edit(false),
+				KeyCode::Char('c') => app.start_edit(true),
+				KeyCode::Char('/') => app.mode = Mode::Filter(app.filter.query.clone()),
+				KeyCode::Esc => app.set_filter(""),
+				_ => {},
+			},
+			Mode::Filter(input) | Mode::EditDescription(input) | Mode::EditCategory(input) => match key.code {
+				KeyCode::Enter => app.finish_edit(),
+				KeyCode::Esc => app.mode = Mode::Browse,
+				KeyCode::Backspace => { input.pop(); },
+				KeyCode::Char(c) => input.push(c),
+				_ => {},
+			},
+		}
+	}
+}
@@ -0,0 +1,68 @@
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Borders, Cell, Paragraph, Row, Table};
+use crate::tui::app::{App, Mode, RowSummary};
+
+static HELP: &str = "↑↓ move  space mark  p pair  u unpair  d description  c category  / filter  q export";
+
+pub fn draw(frame: &mut Frame, app: &mut App) {
+	let [table_area, status_area, help_area] = Layout::vertical([
+		Constraint::Min(3),
+		Constraint::Length(1),
+		Constraint::Length(1),
+	]).areas(frame.area());
+
+	let rows = app.visible.iter()
+		.map(|index| {
+			let summary = RowSummary::new(&app.transactions[*index]);
+			let marker = if app.marked == Some(*index) { "*" } else { "" };
+			let style = match summary.kind {
+				"earlier" | "reversal" => Style::default().fg(Color::DarkGray),
+				"transfer" | "split" => Style::default().fg(Color::Cyan),
+				_ => Style::default(),
+			};
+
+			Row::new(vec![
+				Cell::from(marker),
+				Cell::from(summary.date.to_string()),
+				Cell::from(summary.kind),
+				Cell::from(summary.accounts),
+				Cell::from(Line::from(format!("{} {}", summary.amount, summary.currency)).right_aligned()),
+				Cell::from(summary.description),
+				Cell::from(summary.category),
+			]).style(style)
+		})
+		.collect::<Vec<_>>();
+
+	let title = if app.filter.query.is_empty() {
+		format!(" {} transactions ", app.transactions.len())
+	} else {
+		format!(" {} of {} transactions matching \"{}\" ", app.visible.len(), app.transactions.len(), app.filter.query)
+	};
+
+	let table = Table::new(rows, [
+		Constraint::Length(1),
+		Constraint::Length(10),
+		Constraint::Length(8),
+		Constraint::Percentage(25),
+		Constraint::Length(16),
+		Constraint::Fill(1),
+		Constraint::Percentage(15),
+	])
+		.header(Row::new(["", "Date", "Kind", "Account", "Amount", "Description", "Category"]).style(Style::default().add_modifier(Modifier::BOLD)))
+		.block(Block::default().borders(Borders::ALL).title(title))
+		.row_highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+
+	frame.render_stateful_widget(table, table_area, &mut app.table_state);
+
+	let status = match &app.mode {
+		Mode::Browse => app.status.clone(),
+		Mode::Filter(query) => format!("Filter (account: from: to: min: max: or text): {query}▏"),
+		Mode::EditDescription(description) => format!("Description: {description}▏"),
+		Mode::EditCategory(category) => format!("Category: {category}▏"),
+	};
+	frame.render_widget(Paragraph::new(status), status_area);
+	frame.render_widget(Paragraph::new(HELP).style(Style::default().fg(Color::DarkGray)), help_area);
+}
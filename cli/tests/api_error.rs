@@ -0,0 +1,48 @@
+use reqwest::header::HeaderMap;
+use reqwest::StatusCode;
+use njord::nordigen::api_error::ApiError;
+use njord::nordigen::http_interface::ApiResponse;
+
+fn error(status: u16, body: &str) -> ApiError {
+	ApiError::from_response(ApiResponse {
+		status: StatusCode::from_u16(status).unwrap(),
+		headers: HeaderMap::new(),
+		body: body.as_bytes().to_vec(),
+	})
+}
+
+#[test]
+fn reads_a_top_level_error_body() {
+	let err = error(401, r#"{"summary": "Invalid token", "detail": "Token is invalid or expired", "status_code": 401}"#);
+	assert!(matches!(&err, ApiError::Unauthorized { detail } if detail == "Token is invalid or expired"), "{err:?}");
+
+	let err = error(429, r#"{"summary": "Rate limit exceeded", "status_code": 429}"#);
+	assert!(matches!(&err, ApiError::RateLimited { retry_after: None, detail } if detail == "Rate limit exceeded"), "{err:?}");
+}
+
+#[test]
+fn reads_the_first_field_error_with_the_status_code_next_to_it() {
+	let err = error(400, r#"{
+		"institution_id": {"summary": "Unknown Institution ID", "detail": "NOPE is not a valid institution id"},
+		"status_code": 400
+	}"#);
+	assert!(matches!(&err, ApiError::Validation { field: Some(field), detail } if field == "institution_id" && detail == "NOPE is not a valid institution id"), "{err:?}");
+}
+
+#[test]
+fn prefers_the_status_code_in_the_body_over_the_one_of_the_response() {
+	let err = error(500, r#"{"summary": "End User Agreement expired", "detail": "Access to the account has expired", "status_code": 403}"#);
+	assert!(matches!(&err, ApiError::AccessExpired { detail } if detail == "Access to the account has expired"), "{err:?}");
+
+	let err = error(502, r#"{"summary": "Unknown field", "status_code": 418}"#);
+	assert!(matches!(&err, ApiError::Unexpected { status: StatusCode::IM_A_TEAPOT, summary, .. } if summary == "Unknown field"), "{err:?}");
+}
+
+#[test]
+fn falls_back_to_the_response_status_without_a_json_body() {
+	let err = error(503, "<html>Service Unavailable</html>");
+	assert!(matches!(&err, ApiError::InstitutionDown { detail } if detail == "<html>Service Unavailable</html>"), "{err:?}");
+
+	let err = error(404, "");
+	assert!(matches!(&err, ApiError::NotFound { detail } if detail == "404 Not Found"), "{err:?}");
+}
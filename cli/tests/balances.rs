@@ -0,0 +1,75 @@
+use std::rc::Rc;
+use chrono::NaiveDateTime;
+use njord::nordigen::account::Account;
+use njord::nordigen::balance::fill_running_balances;
+use njord::nordigen::transaction::RawTransaction;
+use njord::report::balances::{history, BalanceRow};
+use rust_decimal::Decimal;
+
+mod common;
+use common::may;
+
+fn account(id: &str, name: &str) -> Rc<Account> {
+	Rc::new(Account { name: Some(name.into()), ..(*common::account(id)).clone() })
+}
+
+fn raw(id: &str, day: u32, amount: i64, booked_at: Option<&str>) -> RawTransaction {
+	RawTransaction {
+		booked_at: booked_at.map(|booked_at| NaiveDateTime::parse_from_str(booked_at, "%Y-%m-%d %H:%M").unwrap()),
+		..common::raw_transaction(id, may(day), Decimal::new(amount, 0))
+	}
+}
+
+fn balances(transactions: &[RawTransaction]) -> Vec<(String, Option<Decimal>)> {
+	transactions.iter().map(|transaction| (transaction.id.clone(), transaction.balance)).collect()
+}
+
+#[test]
+fn fills_same_day_balances_by_booking_time() {
+	// Listed newest first, as many banks do, the booking time tells the order of the day.
+	let mut transactions = [
+		raw("afternoon", 2, -30, Some("2023-05-02 15:00")),
+		raw("morning", 2, 100, Some("2023-05-02 09:00")),
+		raw("before", 1, -20, None),
+	];
+	fill_running_balances(&mut transactions, Some(Decimal::new(150, 0)));
+
+	assert_eq!(balances(&transactions), vec![
+		("afternoon".to_string(), Some(Decimal::new(150, 0))),
+		("morning".to_string(), Some(Decimal::new(180, 0))),
+		("before".to_string(), Some(Decimal::new(80, 0))),
+	]);
+}
+
+#[test]
+fn ends_each_day_on_the_right_balance_whatever_the_order_within_it() {
+	// Without booking times the bank's order is taken as oldest first, which only affects the balances in between.
+	let mut transactions = [
+		raw("second", 2, -30, None),
+		raw("first", 2, 100, None),
+		raw("before", 1, -20, None),
+	];
+	fill_running_balances(&mut transactions, Some(Decimal::new(150, 0)));
+	let raw_transactions = transactions.map(|transaction| (transaction, account("a", "Checking")));
+
+	assert_eq!(history(&raw_transactions, None, None), vec![
+		BalanceRow { date: may(1), account: "Checking".into(), currency: "EUR".into(), balance: Decimal::new(80, 0) },
+		BalanceRow { date: may(2), account: "Checking".into(), currency: "EUR".into(), balance: Decimal::new(150, 0) },
+	]);
+}
+
+#[test]
+fn keeps_accounts_with_the_same_name_apart() {
+	let mut first = [raw("a1", 1, 10, None)];
+	let mut second = [raw("b1", 1, 20, None)];
+	fill_running_balances(&mut first, Some(Decimal::new(110, 0)));
+	fill_running_balances(&mut second, Some(Decimal::new(220, 0)));
+	let raw_transactions = first.map(|transaction| (transaction, account("a", "Savings"))).into_iter()
+		.chain(second.map(|transaction| (transaction, account("b", "Savings"))))
+		.collect::<Vec<_>>();
+
+	let balances = history(&raw_transactions, Some(may(1)), Some(may(1))).into_iter()
+		.map(|row| row.balance)
+		.collect::<Vec<_>>();
+	assert_eq!(balances, vec![Decimal::new(110, 0), Decimal::new(220, 0)]);
+}
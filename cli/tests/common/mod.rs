@@ -0,0 +1,60 @@
+// Every test binary and the benchmark compile these helpers, each using only some of them.
+#![allow(dead_code)]
+
+use std::rc::Rc;
+use chrono::NaiveDate;
+use njord::matcher::Transaction;
+use njord::nordigen::account::Account;
+use njord::nordigen::transaction::RawTransaction;
+use rust_decimal::Decimal;
+
+pub mod synthetic;
+
+// An account at a bank of its own, named after its ID.
+pub fn account(id: &str) -> Rc<Account> {
+	Rc::new(Account {
+		id: id.into(),
+		institution_id: format!("{id}-bank"),
+		bban: None,
+		iban: None,
+		status: "enabled".into(),
+		name: Some(id.into()),
+		display_name: None,
+		owner_name: None,
+	})
+}
+
+// A booked EUR transaction with only its amount known, tests set anything else they need with struct update syntax.
+pub fn raw_transaction(id: &str, date: NaiveDate, amount: Decimal) -> RawTransaction {
+	RawTransaction {
+		date,
+		currency: "EUR".into(),
+		amount,
+		additional_info: None,
+		remittance_information: None,
+		currency_exchange: None,
+		counterparty: None,
+		merchant_category_code: None,
+		balance: None,
+		booked_at: None,
+		id: id.into(),
+	}
+}
+
+pub fn raw(id: &str, account_id: &str, date: NaiveDate, amount: Decimal) -> (RawTransaction, Rc<Account>) {
+	(raw_transaction(id, date, amount), account(account_id))
+}
+
+pub fn may(day: u32) -> NaiveDate {
+	NaiveDate::from_ymd_opt(2023, 5, day).unwrap()
+}
+
+// The sending and the receiving transaction of every transfer, by ID.
+pub fn transfers(transactions: &[Transaction]) -> Vec<(String, String)> {
+	transactions.iter()
+		.filter_map(|transaction| match transaction {
+			Transaction::Transfer(transfer) => Some((transfer.sources[0].id.clone(), transfer.sources[1].id.clone())),
+			_ => None,
+		})
+		.collect()
+}
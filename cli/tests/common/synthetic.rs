@@ -0,0 +1,78 @@
+use std::rc::Rc;
+use chrono::{Duration, NaiveDate};
+use njord::nordigen::account::Account;
+use njord::nordigen::transaction::RawTransaction;
+use rust_decimal::Decimal;
+use super::{account, raw_transaction};
+
+// A small deterministic generator, so runs are comparable without pulling in a random number crate.
+struct Lcg(u64);
+
+impl Lcg {
+	fn next(&mut self, bound: u64) -> u64 {
+		self.0 = self.0.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+		(self.0 >> 33) % bound
+	}
+}
+
+// Card payments, salaries and subscriptions across accounts in EUR and SEK, with transfers between them
+// that arrive a few days late, lose a fee on the way or are converted between currencies.
+pub fn transactions(count: usize, seed: u64) -> Vec<(RawTransaction, Rc<Account>)> {
+	let mut rng = Lcg(seed);
+	let accounts = (0..8)
+		.map(|index| Rc::new(Account {
+			institution_id: format!("institution-{}", index % 3),
+			name: Some(format!("Account {index}")),
+			owner_name: Some("Jane Doe".into()),
+			..(*account(&format!("account-{index}"))).clone()
+		}))
+		.collect::<Vec<_>>();
+	let currency = |account: usize| if account < 6 { "EUR" } else { "SEK" };
+
+	let start = NaiveDate::from_ymd_opt(2020, 1, 1).unwrap();
+	let days = (count as i64 / 20).max(30);
+	let mut transactions = vec![];
+
+	while transactions.len() < count {
+		let id = transactions.len();
+		let date = start + Duration::days(rng.next(days as u64) as i64);
+		let account = rng.next(accounts.len() as u64) as usize;
+		let raw = |id: usize, account: usize, date: NaiveDate, amount: Decimal, info: &str| (RawTransaction {
+			currency: currency(account).into(),
+			additional_info: Some(info.into()),
+			..raw_transaction(&format!("transaction-{id}"), date, amount)
+		}, accounts[account].clone());
+
+		match rng.next(10) {
+			0..=5 => {
+				let amount = Decimal::new(-(rng.next(20_000) as i64 + 1), 2);
+				transactions.push(raw(id, account, date, amount, "Card payment"));
+			},
+			6 => {
+				let amount = Decimal::new([999, 1299, 4500][rng.next(3) as usize], 2);
+				transactions.push(raw(id, account, date, -amount, "Subscription"));
+			},
+			7 => {
+				let amount = Decimal::new(rng.next(400_000) as i64 + 100_000, 2);
+				transactions.push(raw(id, account, date, amount, "Salary"));
+			},
+			_ => {
+				let other = (account + 1 + rng.next(accounts.len() as u64 - 1) as usize) % accounts.len();
+				let sent = Decimal::new(rng.next(100_000) as i64 + 100, 2);
+				let received = match (currency(account), currency(other)) {
+					("EUR", "SEK") => (sent * Decimal::new(115, 1)).round_dp(2),
+					("SEK", "EUR") => (sent / Decimal::new(115, 1)).round_dp(2),
+					_ if rng.next(4) == 0 => sent - Decimal::new(150, 2),
+					_ => sent,
+				};
+				let arrival = date + Duration::days(rng.next(3) as i64);
+
+				transactions.push(raw(id, account, date, -sent, "Transfer out"));
+				transactions.push(raw(id + 1, other, arrival, received, "Transfer in"));
+			},
+		}
+	}
+
+	transactions.truncate(count);
+	transactions
+}
@@ -0,0 +1,59 @@
+use std::rc::Rc;
+use njord::matcher::config::MatcherConfig;
+use njord::matcher::decisions::Decisions;
+use njord::matcher::exchange::ExchangeRate;
+use njord::matcher::{match_transactions, Transaction};
+use njord::nordigen::account::Account;
+use njord::nordigen::transaction::{CurrencyExchange, RawTransaction};
+use rust_decimal::Decimal;
+
+mod common;
+use common::{may, transfers};
+
+fn raw(id: &str, account_id: &str, cents: i64, currency: &str) -> (RawTransaction, Rc<Account>) {
+	let (transaction, account) = common::raw(id, account_id, may(10), Decimal::new(cents, 2));
+	(RawTransaction { currency: currency.into(), ..transaction }, account)
+}
+
+fn matched(raw_transactions: &[(RawTransaction, Rc<Account>)], config: &MatcherConfig) -> Vec<Transaction> {
+	match_transactions(raw_transactions, vec![], config, &mut Decisions::default(), false).unwrap()
+}
+
+#[test]
+fn pairs_currencies_with_a_configured_rate_and_keeps_both_amounts() {
+	let raw_transactions = [raw("euros", "a", -10000, "EUR"), raw("kronor", "b", 114000, "SEK")];
+	assert_eq!(transfers(&matched(&raw_transactions, &MatcherConfig::default())), vec![]);
+
+	let config = MatcherConfig {
+		exchange_rates: vec![ExchangeRate { from: "SEK".into(), to: "EUR".into(), rate: Decimal::new(87, 3) }],
+		..MatcherConfig::default()
+	};
+	let transactions = matched(&raw_transactions, &config);
+	let [Transaction::Transfer(transfer)] = transactions.as_slice() else { panic!("{transactions:?}") };
+	assert_eq!((transfer.amount, transfer.currency.as_str()), (Decimal::new(10000, 2), "EUR"));
+	assert_eq!((transfer.received_amount, transfer.received_currency.as_str()), (Decimal::new(114000, 2), "SEK"));
+
+	let too_far_off = [raw("euros", "a", -10000, "EUR"), raw("kronor", "b", 110000, "SEK")];
+	assert_eq!(transfers(&matched(&too_far_off, &config)), vec![]);
+}
+
+#[test]
+fn prefers_the_rate_the_bank_reported_on_the_transaction() {
+	let (transaction, account) = raw("euros", "a", -10000, "EUR");
+	let currency_exchange = CurrencyExchange {
+		source_currency: "EUR".into(),
+		target_currency: "SEK".into(),
+		unit_currency: None,
+		exchange_rate: Decimal::new(1150, 2),
+	};
+	let raw_transactions = [
+		(RawTransaction { currency_exchange: Some(currency_exchange), ..transaction }, account),
+		raw("kronor", "b", 115000, "SEK"),
+	];
+	let config = MatcherConfig {
+		exchange_rates: vec![ExchangeRate { from: "EUR".into(), to: "SEK".into(), rate: Decimal::new(10, 0) }],
+		..MatcherConfig::default()
+	};
+
+	assert_eq!(transfers(&matched(&raw_transactions, &config)), vec![("euros".to_string(), "kronor".to_string())]);
+}
@@ -0,0 +1,57 @@
+use std::rc::Rc;
+use njord::matcher::config::MatcherConfig;
+use njord::matcher::decisions::Decisions;
+use njord::matcher::{match_transactions, NormalTransaction, Transaction};
+use njord::nordigen::account::Account;
+use njord::nordigen::transaction::RawTransaction;
+use rust_decimal::Decimal;
+
+mod common;
+use common::{may, transfers};
+
+fn raw(id: &str, account_id: &str, amount: i64) -> (RawTransaction, Rc<Account>) {
+	common::raw(id, account_id, may(10), Decimal::new(amount, 0))
+}
+
+fn normal(raw_transaction: &(RawTransaction, Rc<Account>)) -> NormalTransaction {
+	let Transaction::Normal(transaction) = Transaction::from(raw_transaction) else { unreachable!() };
+	transaction
+}
+
+fn matched(raw_transactions: &[(RawTransaction, Rc<Account>)], decisions: &mut Decisions) -> Vec<(String, String)> {
+	transfers(&match_transactions(raw_transactions, vec![], &MatcherConfig::default(), decisions, false).unwrap())
+}
+
+// Through the file the decisions are kept in between runs.
+fn next_run(decisions: &Decisions) -> Decisions {
+	let dir = tempfile::tempdir().unwrap();
+	let path = dir.path().join("decisions.ron");
+	confy::store_path(&path, decisions).unwrap();
+	confy::load_path(&path).unwrap()
+}
+
+#[test]
+fn applies_a_picked_candidate_again_on_the_next_run() {
+	let raw_transactions = [raw("out", "a", -100), raw("first", "b", 100), raw("second", "c", 100)];
+	let [out, first, second] = raw_transactions.each_ref().map(normal);
+
+	let mut decisions = Decisions::default();
+	assert_eq!(matched(&raw_transactions, &mut decisions), vec![]);
+
+	decisions.record(&out, &[&first, &second], Some(&second));
+	assert_eq!(matched(&raw_transactions, &mut next_run(&decisions)), vec![("out".to_string(), "second".to_string())]);
+}
+
+#[test]
+fn keeps_a_rejected_candidate_apart_until_the_decision_is_revoked() {
+	let raw_transactions = [raw("out", "a", -100), raw("in", "b", 100)];
+	let [out, incoming] = raw_transactions.each_ref().map(normal);
+
+	let mut decisions = Decisions::default();
+	decisions.record(&out, &[&incoming], None);
+	let mut decisions = next_run(&decisions);
+	assert_eq!(matched(&raw_transactions, &mut decisions), vec![]);
+
+	assert_eq!(decisions.revoke("in"), 1);
+	assert_eq!(matched(&raw_transactions, &mut decisions), vec![("out".to_string(), "in".to_string())]);
+}
@@ -0,0 +1,100 @@
+use std::rc::Rc;
+use njord::matcher::config::MatcherConfig;
+use njord::matcher::decisions::Decisions;
+use njord::matcher::explain::{explain, Outcome};
+use njord::matcher::rules::TransferRule;
+use njord::nordigen::account::Account;
+use njord::nordigen::transaction::RawTransaction;
+use rust_decimal::Decimal;
+
+mod common;
+use common::may;
+
+fn raw(id: &str, account_id: &str, day: u32, amount: i64, currency: &str) -> (RawTransaction, Rc<Account>) {
+	let (transaction, account) = common::raw(id, account_id, may(day), Decimal::new(amount, 0));
+	(RawTransaction { currency: currency.into(), ..transaction }, account)
+}
+
+fn explained(raw_transactions: &[(RawTransaction, Rc<Account>)], decisions: &Decisions, transaction_id: &str) -> Vec<(String, Outcome, String)> {
+	explained_with(raw_transactions, decisions, transaction_id, false)
+}
+
+fn explained_with(raw_transactions: &[(RawTransaction, Rc<Account>)], decisions: &Decisions, transaction_id: &str, all: bool) -> Vec<(String, Outcome, String)> {
+	let explanation = explain(raw_transactions, vec![], &MatcherConfig::default(), decisions, transaction_id, all).unwrap();
+
+	explanation.candidates.into_iter()
+		.map(|candidate| (candidate.transaction.id, candidate.outcome, candidate.reason.to_string()))
+		.collect()
+}
+
+#[test]
+fn explains_why_each_candidate_is_accepted_or_rejected() {
+	let raw_transactions = [
+		raw("out", "a", 10, -100, "EUR"),
+		raw("same-day", "b", 10, 100, "EUR"),
+		raw("late", "c", 16, 100, "EUR"),
+		raw("short", "d", 11, 98, "EUR"),
+		raw("more", "d", 11, 101, "EUR"),
+		raw("kronor", "e", 10, 1150, "SEK"),
+		raw("same-account", "a", 10, 100, "EUR"),
+		raw("far", "b", 28, 40, "EUR"),
+	];
+
+	assert_eq!(explained(&raw_transactions, &Decisions::default(), "out"), vec![
+		("same-day".to_string(), Outcome::Accepted, "[1.00, 0 days apart] the only candidate scoring at least 1.00".to_string()),
+		("kronor".to_string(), Outcome::Rejected, "no exchange rate from EUR to SEK".to_string()),
+		("short".to_string(), Outcome::Rejected, "amounts differ by 2 EUR, more than the fee tolerance of 0 EUR".to_string()),
+		("more".to_string(), Outcome::Rejected, "1 EUR more is received than was sent".to_string()),
+		("late".to_string(), Outcome::Rejected, "6 days apart, outside the 5 day window".to_string()),
+	]);
+}
+
+#[test]
+fn lists_every_transaction_with_its_reason_when_asked_for_all() {
+	let raw_transactions = [
+		raw("out", "a", 10, -100, "EUR"),
+		raw("same-day", "b", 10, 100, "EUR"),
+		raw("same-account", "a", 11, 100, "EUR"),
+		raw("also-out", "c", 12, -100, "EUR"),
+		raw("far", "b", 28, 40, "EUR"),
+	];
+
+	let explanation = explain(&raw_transactions, vec![], &MatcherConfig::default(), &Decisions::default(), "out", false).unwrap();
+	assert_eq!((explanation.candidates.len(), explanation.same_side, explanation.unrelated), (1, 2, 1));
+
+	assert_eq!(explained_with(&raw_transactions, &Decisions::default(), "out", true), vec![
+		("same-day".to_string(), Outcome::Accepted, "[1.00, 0 days apart] the only candidate scoring at least 1.00".to_string()),
+		("same-account".to_string(), Outcome::Rejected, "in the same account, so not a transfer between accounts".to_string()),
+		("also-out".to_string(), Outcome::Rejected, "money goes the same way, so one side of a transfer cannot be the other".to_string()),
+		("far".to_string(), Outcome::Rejected, "18 days apart, outside the 5 day window".to_string()),
+	]);
+}
+
+#[test]
+fn explains_candidates_that_are_asked_about_or_remembered() {
+	let raw_transactions = [
+		raw("out", "a", 10, -100, "EUR"),
+		raw("first", "b", 10, 100, "EUR"),
+		raw("second", "c", 10, 100, "EUR"),
+	];
+
+	assert_eq!(explained(&raw_transactions, &Decisions::default(), "out"), vec![
+		("first".to_string(), Outcome::Review, "[1.00, 0 days apart] one of 2 candidates scoring at least 1.00".to_string()),
+		("second".to_string(), Outcome::Review, "[1.00, 0 days apart] one of 2 candidates scoring at least 1.00".to_string()),
+	]);
+
+	let decisions = Decisions {
+		decisions: vec![],
+		rules: vec![TransferRule {
+			from_account: "a".into(),
+			to_account: "c".into(),
+			description_contains: None,
+			label: "a -> c".into(),
+			enabled: true,
+		}],
+	};
+	assert_eq!(explained(&raw_transactions, &decisions, "out"), vec![
+		("second".to_string(), Outcome::Accepted, "matches the transfer rule a -> c".to_string()),
+		("first".to_string(), Outcome::Rejected, "another candidate was accepted before or matches a transfer rule".to_string()),
+	]);
+}
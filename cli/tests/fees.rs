@@ -0,0 +1,55 @@
+use std::rc::Rc;
+use njord::export;
+use njord::export::ExportFormat;
+use njord::matcher::config::MatcherConfig;
+use njord::matcher::decisions::Decisions;
+use njord::matcher::{match_transactions, Transaction};
+use njord::nordigen::account::Account;
+use njord::nordigen::transaction::RawTransaction;
+use rust_decimal::Decimal;
+
+mod common;
+use common::{may, transfers};
+
+fn raw(id: &str, account_id: &str, cents: i64) -> (RawTransaction, Rc<Account>) {
+	let (transaction, account) = common::raw(id, account_id, may(10), Decimal::new(cents, 2));
+	(RawTransaction { additional_info: Some(format!("Transfer {id}")), ..transaction }, account)
+}
+
+fn matched(raw_transactions: &[(RawTransaction, Rc<Account>)], config: &MatcherConfig) -> Vec<Transaction> {
+	match_transactions(raw_transactions, vec![], config, &mut Decisions::default(), false).unwrap()
+}
+
+#[test]
+fn pairs_a_transfer_that_lost_a_fee_within_the_tolerance() {
+	let raw_transactions = [raw("sent", "checking", -10000), raw("received", "savings", 9850)];
+	assert_eq!(transfers(&matched(&raw_transactions, &MatcherConfig::default())), vec![]);
+
+	let by_amount = MatcherConfig { fee_tolerance_amount: Decimal::new(2, 0), ..MatcherConfig::default() };
+	let by_percentage = MatcherConfig { fee_tolerance_percentage: 1.5, ..MatcherConfig::default() };
+	for config in [by_amount, by_percentage] {
+		let transactions = matched(&raw_transactions, &config);
+		let [Transaction::Transfer(transfer)] = transactions.as_slice() else { panic!("{transactions:?}") };
+		assert_eq!(transfer.fee, Some(Decimal::new(150, 2)));
+	}
+
+	let more_received = [raw("sent", "checking", -10000), raw("received", "savings", 10100)];
+	assert_eq!(transfers(&matched(&more_received, &MatcherConfig { fee_tolerance_amount: Decimal::new(2, 0), ..MatcherConfig::default() })), vec![]);
+}
+
+#[test]
+fn exports_the_fee_as_its_own_ledger_posting() {
+	let raw_transactions = [raw("sent", "checking", -10000), raw("received", "savings", 9850)];
+	let transactions = matched(&raw_transactions, &MatcherConfig { fee_tolerance_amount: Decimal::new(2, 0), ..MatcherConfig::default() });
+
+	let mut output = vec![];
+	export::write(ExportFormat::Ledger, transactions, &mut output).unwrap();
+
+	assert_eq!(String::from_utf8(output).unwrap(), "\
+2023-05-10 Transfer sent / Transfer received
+    Assets:savings  98.50 EUR
+    Expenses:Fees  1.50 EUR
+    Assets:checking  -100.00 EUR
+
+");
+}
@@ -0,0 +1,77 @@
+use std::cmp::Reverse;
+use std::rc::Rc;
+use njord::export;
+use njord::export::ExportFormat;
+use njord::matcher::config::MatcherConfig;
+use njord::matcher::decisions::Decisions;
+use njord::matcher::exchange::ExchangeRate;
+use njord::matcher::{evaluate_match, match_transactions, pair, Transaction};
+use njord::nordigen::account::Account;
+use njord::nordigen::transaction::RawTransaction;
+use rust_decimal::Decimal;
+
+mod common;
+use common::synthetic;
+
+fn config() -> MatcherConfig {
+	MatcherConfig {
+		fee_tolerance_amount: Decimal::new(200, 2),
+		fee_tolerance_percentage: 0.5,
+		exchange_rates: vec![ExchangeRate { from: "EUR".into(), to: "SEK".into(), rate: Decimal::new(115, 1) }],
+		..MatcherConfig::default()
+	}
+}
+
+// The straightforward scan over every later transaction that the indexed matcher replaced.
+fn match_by_scanning(raw_transactions: &[(RawTransaction, Rc<Account>)], config: &MatcherConfig) -> Vec<Transaction> {
+	let mut transactions = raw_transactions.iter().map(Transaction::from).collect::<Vec<_>>();
+
+	let mut index = 0;
+	while index < transactions.len() {
+		let Transaction::Normal(target) = &transactions[index] else { index += 1; continue };
+
+		let mut scored_candidates = transactions[index + 1..].iter()
+			.enumerate()
+			.filter_map(|(offset, candidate)| match candidate {
+				Transaction::Normal(candidate) => Some((offset, evaluate_match(config, target, candidate)?)),
+				_ => None,
+			})
+			.collect::<Vec<_>>();
+		scored_candidates.sort_unstable_by_key(|(_, score)| Reverse(*score));
+
+		let accepted = scored_candidates.iter()
+			.filter(|(_, score)| score.total >= config.auto_accept_score)
+			.collect::<Vec<_>>();
+		if let [(offset, _)] = accepted.as_slice() {
+			let Transaction::Normal(candidate) = transactions.remove(index + 1 + offset) else { unreachable!() };
+			let Transaction::Normal(target) = transactions.remove(index) else { unreachable!() };
+			transactions.insert(index, Transaction::Transfer(pair(target, candidate)));
+		}
+
+		index += 1;
+	}
+
+	transactions
+}
+
+fn csv(transactions: Vec<Transaction>) -> String {
+	let mut output = vec![];
+	export::write(ExportFormat::Csv, transactions, &mut output).unwrap();
+	String::from_utf8(output).unwrap()
+}
+
+#[test]
+fn indexed_matching_gives_the_same_result_as_scanning() {
+	let config = config();
+
+	for seed in [1, 2] {
+		let raw_transactions = synthetic::transactions(2_000, seed);
+
+		let expected = csv(match_by_scanning(&raw_transactions, &config));
+		let actual = csv(match_transactions(&raw_transactions, vec![], &config, &mut Decisions::default(), false).unwrap());
+
+		assert!(expected.lines().filter(|line| line.contains("Transfer out")).count() > 100);
+		assert_eq!(expected, actual, "seed {seed}");
+	}
+}
+
@@ -0,0 +1,319 @@
+use std::fs;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use std::process::{Child, Command, Output, Stdio};
+use njord::nordigen::config::Config;
+
+struct MockServer {
+	process: Child,
+	base_url: String,
+}
+
+impl MockServer {
+	fn start() -> MockServer {
+		let mut process = Command::new(env!("CARGO_BIN_EXE_njord-mock"))
+			.args(["--port", "0", "--auto-link", "--fixtures", concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/mock")])
+			.stdout(Stdio::piped())
+			.stderr(Stdio::null())
+			.spawn()
+			.expect("unable to start mock server");
+
+		let stdout = process.stdout.take().expect("mock server stdout");
+		let mut line = String::new();
+		BufReader::new(stdout).read_line(&mut line).expect("mock server address");
+		let base_url = line.trim().trim_start_matches("Listening on ").to_string();
+
+		MockServer { process, base_url }
+	}
+}
+
+impl Drop for MockServer {
+	fn drop(&mut self) {
+		let _ = self.process.kill();
+		let _ = self.process.wait();
+	}
+}
+
+fn write_config(config_home: &Path) {
+	write_config_with(config_home, "");
+}
+
+// The extra fields go at the end of the config, after the selected institutions.
+fn write_config_with(config_home: &Path, extra_fields: &str) {
+	let config_dir = config_home.join("njord");
+	fs::create_dir_all(&config_dir).unwrap();
+	fs::write(config_dir.join("config.ron"), format!(r#"(
+	client_credentials: Some((id: "mock-id", secret: "mock-secret")),
+	token: None,
+	selected_institutions: [(
+		id: "SANDBOXFINANCE_SFIN0000",
+		name: "Sandbox Finance",
+		countries: ["XX"],
+		requisition_id: None,
+		observed_transactions: {{}},
+	)],
+{extra_fields}
+)"#)).unwrap();
+}
+
+fn read_config(config_home: &Path) -> Config {
+	confy::load_path(config_home.join("njord").join("config.ron")).unwrap()
+}
+
+fn run_njord(config_home: &Path, base_url: &str, args: &[&str]) -> Output {
+	Command::new(env!("CARGO_BIN_EXE_njord"))
+		.args(["--reuse", "--base-url", base_url])
+		.args(args)
+		.env("XDG_CONFIG_HOME", config_home)
+		.env("HOME", config_home)
+		.stdin(Stdio::null())
+		.output()
+		.expect("unable to run njord")
+}
+
+#[test]
+fn fetches_and_matches_sandbox_transactions() {
+	let mock = MockServer::start();
+	let config_home = tempfile::tempdir().unwrap();
+	write_config(config_home.path());
+
+	let output = run_njord(config_home.path(), &mock.base_url, &[]);
+	let stdout = String::from_utf8(output.stdout).unwrap();
+	let stderr = String::from_utf8(output.stderr).unwrap();
+	assert!(output.status.success(), "njord failed\n{stderr}");
+
+	let rows = stdout.lines().collect::<Vec<_>>();
+	assert_eq!(rows[0], "date,account_from,account_to,amount,currency,description,received_amount,received_currency,fee,category,payee,tags,memo,balance,received_balance");
+	assert!(rows.contains(&"2023-04-03,Main Account,Savings Account,250.00,EUR,from: Transfer to savings to: Transfer from main account,250.00,EUR,,,,,,750.00,1250.00"), "{stdout}");
+	assert!(rows.contains(&"2023-04-05,Main Account,,-45.20,EUR,Card purchase Freshto Ltd,,,,,Freshto Ltd,,,704.80,"), "{stdout}");
+	assert_eq!(rows.len(), 1 + 5, "{stdout}");
+}
+
+#[test]
+fn skips_transactions_seen_in_earlier_runs() {
+	let mock = MockServer::start();
+	let config_home = tempfile::tempdir().unwrap();
+	write_config(config_home.path());
+
+	let first = run_njord(config_home.path(), &mock.base_url, &[]);
+	assert!(first.status.success(), "{}", String::from_utf8_lossy(&first.stderr));
+
+	let second = run_njord(config_home.path(), &mock.base_url, &[]);
+	assert!(second.status.success(), "{}", String::from_utf8_lossy(&second.stderr));
+	assert_eq!(String::from_utf8(second.stdout).unwrap(), "");
+}
+
+#[test]
+fn categorizes_transactions_with_rules_and_payee_aliases() {
+	let mock = MockServer::start();
+	let config_home = tempfile::tempdir().unwrap();
+	write_config(config_home.path());
+	fs::write(config_home.path().join("njord").join("rules.ron"), r#"(
+	rules: [
+		(name: "Groceries", merchant_category_code: Some("5411"), category: Some("Expenses:Groceries"), payee: Some("Freshto"), tags: ["food"]),
+		(name: "Card", description: Some("^Card purchase"), category: Some("Expenses:Shopping"), tags: ["card"]),
+		(name: "Salary", counterparty: Some("employer"), min_amount: Some("1000"), category: Some("Income:Salary"), memo: Some("April pay")),
+	],
+)"#).unwrap();
+	fs::write(config_home.path().join("njord").join("payees.ron"), r#"(
+	aliases: [(payee: "Streamflix Inc", patterns: ["streamflix"])],
+)"#).unwrap();
+
+	let output = run_njord(config_home.path(), &mock.base_url, &[]);
+	let stdout = String::from_utf8(output.stdout).unwrap();
+	assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+	let rows = stdout.lines().collect::<Vec<_>>();
+	assert!(rows.contains(&"2023-04-05,Main Account,,-45.20,EUR,Card purchase Freshto Ltd,,,,Expenses:Groceries,Freshto,food card,,704.80,"), "{stdout}");
+	assert!(rows.contains(&"2023-04-10,Main Account,,1500.00,EUR,Salary Sandbox Employer,,,,Income:Salary,Sandbox Employer,,April pay,2204.80,"), "{stdout}");
+	assert!(rows.contains(&"2023-04-12,Main Account,,-12.99,EUR,Card purchase Streamflix,,,,Expenses:Shopping,Streamflix Inc,card,,2191.81,"), "{stdout}");
+	assert!(rows.contains(&"2023-04-30,Savings Account,,3.10,EUR,Interest,,,,,Interest,,,1253.10,"), "{stdout}");
+}
+
+#[test]
+fn reports_income_and_expenses_without_transfers() {
+	let mock = MockServer::start();
+	let config_home = tempfile::tempdir().unwrap();
+	write_config(config_home.path());
+
+	let output = run_njord(config_home.path(), &mock.base_url, &["report", "--output", "csv"]);
+	let stdout = String::from_utf8(output.stdout).unwrap();
+	assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+	assert_eq!(stdout, "\
+month,group,name,currency,income,expenses,net
+2023-04,account,Main Account,EUR,1500.00,58.19,1441.81
+2023-04,account,Savings Account,EUR,3.10,0.00,3.10
+2023-04,category,Uncategorized,EUR,1503.10,58.19,1444.91
+2023-04,total,Total,EUR,1503.10,58.19,1444.91
+");
+}
+
+#[test]
+fn prints_daily_balances_from_the_current_balance_backwards() {
+	let mock = MockServer::start();
+	let config_home = tempfile::tempdir().unwrap();
+	write_config(config_home.path());
+
+	let output = run_njord(config_home.path(), &mock.base_url, &["balances", "--history", "--from", "2023-04-02", "--until", "2023-04-05", "--output", "csv"]);
+	let stdout = String::from_utf8(output.stdout).unwrap();
+	assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+	assert_eq!(stdout, "\
+date,account,currency,balance
+2023-04-02,Main Account,EUR,1000.00
+2023-04-02,Savings Account,EUR,1000.00
+2023-04-03,Main Account,EUR,750.00
+2023-04-03,Savings Account,EUR,1250.00
+2023-04-04,Main Account,EUR,750.00
+2023-04-04,Savings Account,EUR,1250.00
+2023-04-05,Main Account,EUR,704.80
+2023-04-05,Savings Account,EUR,1250.00
+");
+
+	let export = run_njord(config_home.path(), &mock.base_url, &[]);
+	assert!(export.status.success(), "{}", String::from_utf8_lossy(&export.stderr));
+	assert_eq!(String::from_utf8(export.stdout).unwrap().lines().count(), 1 + 5);
+}
+
+#[test]
+fn stores_a_net_worth_snapshot_in_the_base_currency() {
+	let mock = MockServer::start();
+	let config_home = tempfile::tempdir().unwrap();
+	write_config(config_home.path());
+	fs::write(config_home.path().join("njord").join("rates.ron"), r#"(base_currency: "SEK", rates: [(from: "EUR", to: "SEK", rate: "11.25")])"#).unwrap();
+
+	let output = run_njord(config_home.path(), &mock.base_url, &["net-worth", "--output", "csv"]);
+	let stdout = String::from_utf8(output.stdout).unwrap();
+	assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+	let today = chrono::Local::now().date_naive();
+	assert_eq!(stdout, format!("\
+date,account,balance,currency,value,base_currency
+{today},Main Account,2191.81,EUR,24657.86,SEK
+{today},Savings Account,1253.10,EUR,14097.38,SEK
+"));
+
+	let history = run_njord(config_home.path(), &mock.base_url, &["net-worth", "--history", "--output", "csv"]);
+	assert!(history.status.success(), "{}", String::from_utf8_lossy(&history.stderr));
+	assert_eq!(String::from_utf8(history.stdout).unwrap(), format!("date,total,base_currency\n{today},38755.24,SEK\n"));
+}
+
+#[test]
+fn tracks_spending_against_category_budgets() {
+	let mock = MockServer::start();
+	let config_home = tempfile::tempdir().unwrap();
+	write_config_with(config_home.path(), r#"	budgets: [
+		(category: "Expenses:Food", amount: "40", currency: "EUR"),
+		(category: "Expenses:Subscriptions", amount: "20", currency: "EUR"),
+		(category: "Expenses:Food", amount: "100", currency: "EUR", month: Some("2023-05")),
+	],"#);
+	fs::write(config_home.path().join("njord").join("rules.ron"), r#"(
+	rules: [
+		(name: "Groceries", merchant_category_code: Some("5411"), category: Some("Expenses:Food:Groceries")),
+		(name: "Streaming", merchant_category_code: Some("4899"), category: Some("Expenses:Subscriptions")),
+	],
+)"#).unwrap();
+
+	let output = run_njord(config_home.path(), &mock.base_url, &["budget", "--month", "2023-04", "--output", "csv"]);
+	let stdout = String::from_utf8(output.stdout).unwrap();
+	let stderr = String::from_utf8(output.stderr).unwrap();
+	assert!(output.status.success(), "{stderr}");
+
+	assert_eq!(stdout, "\
+month,category,currency,budgeted,spent,remaining
+2023-04,Expenses:Food,EUR,40.00,45.20,-5.20
+2023-04,Expenses:Subscriptions,EUR,20.00,12.99,7.01
+");
+	assert!(stderr.contains("Over budget for Expenses:Food in 2023-04, by 5.20 EUR"), "{stderr}");
+}
+
+#[test]
+fn reports_leave_the_transactions_to_the_next_export() {
+	let mock = MockServer::start();
+	let config_home = tempfile::tempdir().unwrap();
+	write_config(config_home.path());
+
+	for (report, remaining) in [(&["report", "--output", "csv"][..], 3), (&["recurring"], 2), (&["budget"], 1)] {
+		let output = run_njord(config_home.path(), &mock.base_url, report);
+		assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+		let institution = &read_config(config_home.path()).selected_institutions[0];
+		assert!(institution.observed_transactions.values().all(|observed| observed.is_empty()), "{:?}", institution.observed_transactions);
+		assert_eq!(institution.transaction_rate_limits.len(), 2);
+		assert!(institution.transaction_rate_limits.values().all(|rate_limit| rate_limit.remaining == remaining), "{:?}", institution.transaction_rate_limits);
+	}
+
+	let export = run_njord(config_home.path(), &mock.base_url, &["--force"]);
+	let stdout = String::from_utf8(export.stdout).unwrap();
+	assert!(export.status.success(), "{}", String::from_utf8_lossy(&export.stderr));
+	assert_eq!(stdout.lines().count(), 1 + 5, "{stdout}");
+}
+
+#[test]
+fn explains_a_transaction_again_without_losing_it_for_the_export() {
+	let mock = MockServer::start();
+	let config_home = tempfile::tempdir().unwrap();
+	write_config(config_home.path());
+
+	for _ in 0..2 {
+		let explain = run_njord(config_home.path(), &mock.base_url, &["match", "--explain", "2023040301926010-1"]);
+		let stdout = String::from_utf8(explain.stdout).unwrap();
+		assert!(explain.status.success(), "{}", String::from_utf8_lossy(&explain.stderr));
+		assert!(stdout.contains("accepted: "), "{stdout}");
+	}
+
+	let export = run_njord(config_home.path(), &mock.base_url, &[]);
+	let stdout = String::from_utf8(export.stdout).unwrap();
+	assert!(export.status.success(), "{}", String::from_utf8_lossy(&export.stderr));
+	assert_eq!(stdout.lines().count(), 1 + 5, "{stdout}");
+}
+
+#[test]
+fn tests_rules_against_the_transactions_the_next_export_gets() {
+	let mock = MockServer::start();
+	let config_home = tempfile::tempdir().unwrap();
+	write_config(config_home.path());
+	fs::write(config_home.path().join("njord").join("rules.ron"), r#"(
+	rules: [(name: "Groceries", merchant_category_code: Some("5411"), category: Some("Expenses:Groceries"))],
+)"#).unwrap();
+
+	let output = run_njord(config_home.path(), &mock.base_url, &["rules", "test"]);
+	let stdout = String::from_utf8(output.stdout).unwrap();
+	let stderr = String::from_utf8(output.stderr).unwrap();
+	assert!(output.status.success(), "{stderr}");
+	assert!(stdout.contains("    rules: Groceries\n    category: Expenses:Groceries, payee: Freshto Ltd"), "{stdout}");
+	assert!(stderr.contains("1 of 4 fetched and kept transactions matched by a rule"), "{stderr}");
+
+	let export = run_njord(config_home.path(), &mock.base_url, &[]);
+	assert!(export.status.success(), "{}", String::from_utf8_lossy(&export.stderr));
+	assert_eq!(String::from_utf8(export.stdout).unwrap().lines().count(), 1 + 5);
+}
+
+#[test]
+fn records_without_secrets_or_account_numbers_and_replays_the_same_export() {
+	let mock = MockServer::start();
+	let config_home = tempfile::tempdir().unwrap();
+	write_config(config_home.path());
+	let recording = tempfile::tempdir().unwrap();
+	let recording_dir = recording.path().to_str().unwrap();
+
+	let recorded = run_njord(config_home.path(), &mock.base_url, &["--record", recording_dir]);
+	assert!(recorded.status.success(), "{}", String::from_utf8_lossy(&recorded.stderr));
+	assert_eq!(String::from_utf8_lossy(&recorded.stdout).lines().count(), 1 + 5);
+
+	let secrets = ["mock-id", "mock-secret", "mock-access-", "mock-refresh-", "GL3343697694912188", "GL0865354374424724", "GL5604449876543210"];
+	for entry in fs::read_dir(recording.path()).unwrap() {
+		let path = entry.unwrap().path();
+		let content = fs::read_to_string(&path).unwrap();
+		for secret in secrets {
+			assert!(!content.contains(secret), "{secret} in {}\n{content}", path.display());
+		}
+	}
+
+	let replay_home = tempfile::tempdir().unwrap();
+	write_config(replay_home.path());
+	let replayed = run_njord(replay_home.path(), &mock.base_url, &["--replay", recording_dir]);
+	assert!(replayed.status.success(), "{}", String::from_utf8_lossy(&replayed.stderr));
+	assert_eq!(String::from_utf8(replayed.stdout).unwrap(), String::from_utf8(recorded.stdout).unwrap());
+}
@@ -0,0 +1,63 @@
+use std::rc::Rc;
+use njord::matcher::assignment::MatchingStrategy;
+use njord::matcher::config::MatcherConfig;
+use njord::matcher::decisions::Decisions;
+use njord::matcher::match_transactions;
+use njord::nordigen::account::Account;
+use njord::nordigen::transaction::RawTransaction;
+use rust_decimal::Decimal;
+
+mod common;
+use common::may;
+
+fn raw(id: &str, account_id: &str, day: u32, amount: i64) -> (RawTransaction, Rc<Account>) {
+	common::raw(id, account_id, may(day), Decimal::new(amount, 0))
+}
+
+fn config(matching_strategy: MatchingStrategy) -> MatcherConfig {
+	MatcherConfig {
+		matching_strategy,
+		auto_accept_score: 0.5,
+		..MatcherConfig::default()
+	}
+}
+
+fn transfers(raw_transactions: &[(RawTransaction, Rc<Account>)], matching_strategy: MatchingStrategy) -> Vec<(String, String)> {
+	common::transfers(&match_transactions(raw_transactions, vec![], &config(matching_strategy), &mut Decisions::default(), false).unwrap())
+}
+
+#[test]
+fn optimal_matching_pairs_a_cluster_with_a_single_best_assignment() {
+	// Both senders could take either receiver, but only one way around pairs everything on the same day.
+	// Going in order, the first receiver settles for the later sender as the earlier one was left for a question.
+	let raw_transactions = [
+		raw("a-out", "a", 2, -100),
+		raw("b-in", "b", 2, 100),
+		raw("c-in", "c", 4, 100),
+		raw("d-out", "d", 4, -100),
+	];
+
+	assert_eq!(transfers(&raw_transactions, MatchingStrategy::Greedy), vec![
+		("d-out".to_string(), "b-in".to_string()),
+	]);
+	assert_eq!(transfers(&raw_transactions, MatchingStrategy::Optimal), vec![
+		("a-out".to_string(), "b-in".to_string()),
+		("d-out".to_string(), "c-in".to_string()),
+	]);
+}
+
+#[test]
+fn optimal_matching_leaves_a_cluster_with_several_best_assignments() {
+	// Either sender fits the receiver equally well, so it takes a question to tell which one it was.
+	let raw_transactions = [
+		raw("a-out", "a", 2, -100),
+		raw("b-in", "b", 2, 100),
+		raw("c-out", "c", 2, -100),
+		raw("d-out", "d", 9, -50),
+		raw("e-in", "e", 9, 50),
+	];
+
+	assert_eq!(transfers(&raw_transactions, MatchingStrategy::Optimal), vec![
+		("d-out".to_string(), "e-in".to_string()),
+	]);
+}
@@ -0,0 +1,50 @@
+use std::rc::Rc;
+use njord::matcher::config::MatcherConfig;
+use njord::matcher::decisions::Decisions;
+use njord::matcher::own_accounts::OwnAccount;
+use njord::matcher::{match_transactions, Transaction};
+use njord::nordigen::account::Account;
+use njord::nordigen::transaction::{Counterparty, RawTransaction};
+use rust_decimal::Decimal;
+
+mod common;
+use common::may;
+
+fn raw(id: &str, amount: i64, counterparty: Counterparty) -> (RawTransaction, Rc<Account>) {
+	let (transaction, account) = common::raw(id, "checking", may(10), Decimal::new(amount, 0));
+	(RawTransaction { counterparty: Some(counterparty), ..transaction }, account)
+}
+
+fn config() -> MatcherConfig {
+	MatcherConfig {
+		own_accounts: vec![
+			OwnAccount { name: "Broker".into(), iban: Some("GL08 6535 4374 4247 24".into()), bban: None, counterparty_name: None },
+			OwnAccount { name: "Pension".into(), iban: None, bban: None, counterparty_name: Some("Pension Fund".into()) },
+		],
+		..MatcherConfig::default()
+	}
+}
+
+// The transfers as from and to account names.
+fn transfers(raw_transactions: &[(RawTransaction, Rc<Account>)]) -> Vec<(Option<String>, Option<String>)> {
+	match_transactions(raw_transactions, vec![], &config(), &mut Decisions::default(), false).unwrap().into_iter()
+		.filter_map(|transaction| match transaction {
+			Transaction::Transfer(transfer) => Some((transfer.from.name.clone(), transfer.to.name.clone())),
+			_ => None,
+		})
+		.collect()
+}
+
+#[test]
+fn transfers_to_own_accounts_at_banks_that_are_not_fetched() {
+	let raw_transactions = [
+		raw("to-broker", -500, Counterparty { name: None, iban: Some("gl0865354374424724".into()), bban: None }),
+		raw("from-pension", 300, Counterparty { name: Some(" pension fund ".into()), iban: None, bban: None }),
+		raw("groceries", -40, Counterparty { name: Some("Freshto Ltd".into()), iban: Some("GL5604449876543210".into()), bban: None }),
+	];
+
+	assert_eq!(transfers(&raw_transactions), vec![
+		(Some("checking".to_string()), Some("Broker".to_string())),
+		(Some("Pension".to_string()), Some("checking".to_string())),
+	]);
+}
@@ -0,0 +1,23 @@
+use njord::payees::clean::clean;
+
+#[test]
+fn cleans_bank_descriptions_down_to_the_payee() {
+	let examples = [
+		("KORTKÖP 230412 ICA NARA STOCKHOLM", "Ica Nara Stockholm"),
+		("Card purchase Freshto Ltd", "Freshto Ltd"),
+		("VISA *4821 STARBUCKS 12.04 REF 99812", "Starbucks"),
+		("Kartenzahlung 2023-04-12 REWE Markt GmbH", "REWE Markt GmbH"),
+		("POS 7-ELEVEN 1234567890", "7-Eleven"),
+		("Interest", "Interest"),
+		("Netflix.com 4029357733", "Netflix.com"),
+	];
+
+	for (description, payee) in examples {
+		assert_eq!(clean(description, &[]), payee, "{description}");
+	}
+}
+
+#[test]
+fn strips_configured_prefixes() {
+	assert_eq!(clean("ÜBERWEISUNG Hausverwaltung Müller", &["Überweisung".to_string()]), "Hausverwaltung Müller");
+}
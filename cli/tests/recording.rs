@@ -0,0 +1,37 @@
+use std::fs;
+use reqwest::header::HeaderMap;
+use reqwest::StatusCode;
+use njord::nordigen::http_interface::ApiResponse;
+use njord::nordigen::recording::Recorder;
+
+fn record(body: &str) -> String {
+	let dir = tempfile::tempdir().unwrap();
+	let recorder = Recorder::new(dir.path()).unwrap();
+	let response = ApiResponse { status: StatusCode::OK, headers: HeaderMap::new(), body: body.as_bytes().to_vec() };
+	recorder.record("GET", "/accounts/1/transactions/", None, &response).unwrap();
+
+	let path = fs::read_dir(dir.path()).unwrap().next().unwrap().unwrap().path();
+	fs::read_to_string(path).unwrap()
+}
+
+#[test]
+fn pseudonymizes_account_numbers_in_fields_and_text() {
+	let recording = record(r#"{
+		"debtorAccount": { "iban": "GL5604449876543210" },
+		"remittanceInformationUnstructured": "Refund to GL56 0444 9876 5432 10 and SE4550000000058398257466"
+	}"#);
+
+	assert!(!recording.contains("GL5604449876543210"), "{recording}");
+	assert!(!recording.contains("GL56 0444"), "{recording}");
+	assert!(!recording.contains("SE4550000000058398257466"), "{recording}");
+	assert!(recording.contains(r#""iban": "REDACTED000001""#), "{recording}");
+}
+
+#[test]
+fn keeps_structured_creditor_references() {
+	let recording = record(r#"{ "remittanceInformationStructured": "RF18 5390 0754 7034", "remittanceInformationUnstructured": "Invoice RF18539007547034" }"#);
+
+	assert!(recording.contains("RF18 5390 0754 7034"), "{recording}");
+	assert!(recording.contains("Invoice RF18539007547034"), "{recording}");
+	assert!(!recording.contains("REDACTED"), "{recording}");
+}
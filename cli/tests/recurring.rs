@@ -0,0 +1,104 @@
+use std::rc::Rc;
+use chrono::NaiveDate;
+use njord::matcher::config::MatcherConfig;
+use njord::matcher::decisions::Decisions;
+use njord::matcher::match_transactions;
+use njord::nordigen::account::Account;
+use njord::nordigen::transaction::RawTransaction;
+use njord::report::recurring::{detect, Cadence};
+use rust_decimal::Decimal;
+
+mod common;
+
+fn date(month: u32, day: u32) -> NaiveDate {
+	NaiveDate::from_ymd_opt(2023, month, day).unwrap()
+}
+
+fn date_in(year: i32, month: u32, day: u32) -> NaiveDate {
+	NaiveDate::from_ymd_opt(year, month, day).unwrap()
+}
+
+fn raw(id: &str, date: NaiveDate, cents: i64, description: &str) -> (RawTransaction, Rc<Account>) {
+	let (transaction, account) = common::raw(id, "main", date, Decimal::new(cents, 2));
+	(RawTransaction { additional_info: Some(description.into()), ..transaction }, account)
+}
+
+fn recurring(raw_transactions: &[(RawTransaction, Rc<Account>)], today: NaiveDate) -> Vec<(String, Cadence, NaiveDate, Option<Decimal>, u32)> {
+	let transactions = match_transactions(raw_transactions, vec![], &MatcherConfig::default(), &mut Decisions::default(), false).unwrap();
+
+	detect(&transactions, today).into_iter()
+		.map(|row| (row.payee, row.cadence, row.next_date, row.previous_amount, row.missed))
+		.collect()
+}
+
+#[test]
+fn detects_monthly_and_weekly_charges_with_their_next_date() {
+	let raw_transactions = [
+		raw("s1", date(1, 12), -1299, "Streamflix"),
+		raw("s2", date(2, 13), -1299, "Streamflix"),
+		raw("s3", date(3, 12), -1299, "Streamflix"),
+		raw("s4", date(4, 11), -1499, "Streamflix"),
+		raw("g1", date(3, 20), -1000, "Gym"),
+		raw("g2", date(3, 27), -1000, "Gym"),
+		raw("g3", date(4, 3), -1000, "Gym"),
+		raw("g4", date(4, 10), -1000, "Gym"),
+		raw("f1", date(3, 2), -4520, "Freshto"),
+		raw("f2", date(3, 9), -1730, "Freshto"),
+		raw("f3", date(3, 16), -8800, "Freshto"),
+		raw("c1", date(2, 1), -500, "Coffee"),
+		raw("c2", date(2, 19), -500, "Coffee"),
+		raw("c3", date(4, 2), -500, "Coffee"),
+	];
+
+	assert_eq!(recurring(&raw_transactions, date(4, 12)), vec![
+		("Gym".to_string(), Cadence::Weekly, date(4, 17), None, 0),
+		("Streamflix".to_string(), Cadence::Monthly, date(5, 11), Some(Decimal::new(-1299, 2)), 0),
+	]);
+}
+
+#[test]
+fn counts_gaps_and_overdue_charges_as_missed() {
+	let raw_transactions = [
+		raw("r1", date(1, 1), -90000, "Rent"),
+		raw("r2", date(2, 1), -90000, "Rent"),
+		raw("r3", date(3, 1), -90000, "Rent"),
+		raw("r4", date(5, 1), -90000, "Rent"),
+	];
+
+	assert_eq!(recurring(&raw_transactions, date(7, 20)), vec![
+		("Rent".to_string(), Cadence::Monthly, date(8, 1), None, 3),
+	]);
+}
+
+#[test]
+fn keeps_reporting_a_charge_after_a_big_price_change() {
+	let raw_transactions = [
+		raw("s1", date(1, 12), -999, "Streamflix"),
+		raw("s2", date(2, 12), -999, "Streamflix"),
+		raw("s3", date(3, 12), -999, "Streamflix"),
+		raw("s4", date(4, 12), -1499, "Streamflix"),
+		raw("b1", date(1, 20), -4000, "Bookshop"),
+		raw("b2", date(2, 20), -900, "Bookshop"),
+		raw("b3", date(3, 20), -4000, "Bookshop"),
+		raw("b4", date(4, 20), -900, "Bookshop"),
+	];
+
+	assert_eq!(recurring(&raw_transactions, date(4, 25)), vec![
+		("Streamflix".to_string(), Cadence::Monthly, date(5, 12), Some(Decimal::new(-999, 2)), 0),
+	]);
+}
+
+#[test]
+fn needs_more_than_a_year_of_transactions_for_a_yearly_charge() {
+	// With the 90 days most banks return, a yearly charge shows up once and is not told apart from a one-off.
+	let within_90_days = [raw("i1", date_in(2023, 3, 1), -24000, "Insurance")];
+	assert_eq!(recurring(&within_90_days, date_in(2023, 5, 1)), vec![]);
+
+	let over_two_years = [
+		raw("i1", date_in(2022, 3, 1), -23000, "Insurance"),
+		raw("i2", date_in(2023, 3, 3), -24000, "Insurance"),
+	];
+	assert_eq!(recurring(&over_two_years, date_in(2023, 5, 1)), vec![
+		("Insurance".to_string(), Cadence::Yearly, date_in(2024, 3, 3), Some(Decimal::new(-23000, 2)), 0),
+	]);
+}
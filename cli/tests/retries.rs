@@ -0,0 +1,43 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use njord::nordigen::config::HttpConfig;
+use njord::nordigen::http_interface::{institutions, requisitions, ApiClient};
+use tiny_http::{Response, Server};
+
+// A server failing the first requests with a 503, counting every request it gets.
+fn failing_server(failures: usize) -> (ApiClient, Arc<AtomicUsize>) {
+	let server = Server::http("127.0.0.1:0").unwrap();
+	let base_url = format!("http://{}/api/v2", server.server_addr().to_ip().unwrap());
+	let requests = Arc::new(AtomicUsize::new(0));
+
+	let counted = requests.clone();
+	thread::spawn(move || for request in server.incoming_requests() {
+		let response = if counted.fetch_add(1, Ordering::SeqCst) < failures {
+			Response::from_string(r#"{"summary": "Service unavailable", "detail": "Try again later", "status_code": 503}"#).with_status_code(503)
+		} else {
+			Response::from_string("[]")
+		};
+		let _ = request.respond(response);
+	});
+
+	let client = ApiClient::new(&HttpConfig { base_url, ..HttpConfig::default() }).unwrap();
+	(client, requests)
+}
+
+#[test]
+fn retries_a_get_that_failed_on_the_server() {
+	let (client, requests) = failing_server(1);
+
+	assert_eq!(institutions::list(&client, "token").unwrap().len(), 0);
+	assert_eq!(requests.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn sends_a_post_that_failed_on_the_server_only_once() {
+	let (client, requests) = failing_server(1);
+
+	let body = requisitions::PostRequestBody { redirect: "http://localhost", institution_id: "SANDBOXFINANCE_SFIN0000" };
+	assert!(requisitions::post(&client, "token", &body).is_err());
+	assert_eq!(requests.load(Ordering::SeqCst), 1);
+}
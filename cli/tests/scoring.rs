@@ -0,0 +1,47 @@
+use std::rc::Rc;
+use njord::matcher::config::MatcherConfig;
+use njord::matcher::decisions::Decisions;
+use njord::matcher::match_transactions;
+use njord::nordigen::account::Account;
+use njord::nordigen::transaction::RawTransaction;
+use rust_decimal::Decimal;
+
+mod common;
+use common::{may, transfers};
+
+fn raw(id: &str, account_id: &str, day: u32, amount: i64) -> (RawTransaction, Rc<Account>) {
+	common::raw(id, account_id, may(day), Decimal::new(amount, 0))
+}
+
+fn matched(raw_transactions: &[(RawTransaction, Rc<Account>)], config: &MatcherConfig) -> Vec<(String, String)> {
+	transfers(&match_transactions(raw_transactions, vec![], config, &mut Decisions::default(), false).unwrap())
+}
+
+#[test]
+fn accepts_candidates_days_apart_once_they_score_above_the_threshold() {
+	// Two days apart in a five day window scores 0.6.
+	let raw_transactions = [raw("out", "a", 10, -100), raw("in", "b", 12, 100)];
+
+	assert_eq!(matched(&raw_transactions, &MatcherConfig::default()), vec![]);
+	assert_eq!(matched(&raw_transactions, &MatcherConfig { auto_accept_score: 0.6, ..MatcherConfig::default() }), vec![
+		("out".to_string(), "in".to_string()),
+	]);
+	assert_eq!(matched(&raw_transactions, &MatcherConfig { auto_accept_score: 0.0, date_window_days: 2, ..MatcherConfig::default() }), vec![]);
+}
+
+#[test]
+fn prefers_the_candidate_at_the_same_institution_when_weighted() {
+	let (transaction, account) = raw("out", "a", 10, -100);
+	let shared_bank = |account: Rc<Account>| Rc::new(Account { institution_id: "shared-bank".into(), ..(*account).clone() });
+	let (same_bank, same_bank_account) = raw("same-bank", "b", 10, 100);
+	let raw_transactions = [
+		(transaction, shared_bank(account)),
+		raw("other-bank", "c", 10, 100),
+		(same_bank, shared_bank(same_bank_account)),
+	];
+
+	assert_eq!(matched(&raw_transactions, &MatcherConfig::default()), vec![]);
+	assert_eq!(matched(&raw_transactions, &MatcherConfig { same_institution_weight: 0.5, auto_accept_score: 1.5, ..MatcherConfig::default() }), vec![
+		("out".to_string(), "same-bank".to_string()),
+	]);
+}
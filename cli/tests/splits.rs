@@ -0,0 +1,50 @@
+use njord::matcher::config::MatcherConfig;
+use njord::matcher::split::find_splits;
+use njord::matcher::{NormalTransaction, Transaction};
+use rust_decimal::Decimal;
+
+mod common;
+use common::may;
+
+fn normal(id: &str, account_id: &str, day: u32, amount: i64) -> NormalTransaction {
+	let Transaction::Normal(transaction) = Transaction::from(&common::raw(id, account_id, may(day), Decimal::new(amount, 0))) else { unreachable!() };
+	transaction
+}
+
+fn splits(config: &MatcherConfig, candidates: &[NormalTransaction], target: &NormalTransaction) -> Vec<Vec<String>> {
+	find_splits(config, candidates.iter().enumerate(), target).into_iter()
+		.map(|split| split.legs.iter().map(|leg| leg.transaction.id.clone()).collect())
+		.collect()
+}
+
+#[test]
+fn finds_the_legs_that_add_up_to_the_target_fewest_first() {
+	let target = normal("sent", "checking", 10, -300);
+	let candidates = [
+		normal("hundred", "savings", 10, 100),
+		normal("two-hundred", "broker", 11, 200),
+		normal("hundred-fifty", "savings", 12, 150),
+		normal("fifty", "pension", 10, 50),
+		normal("same-account", "checking", 10, 200),
+		normal("outgoing", "savings", 10, -200),
+		normal("too-late", "broker", 20, 200),
+	];
+
+	assert_eq!(splits(&MatcherConfig::default(), &candidates, &target), vec![
+		vec!["hundred".to_string(), "two-hundred".to_string()],
+		vec!["hundred".to_string(), "hundred-fifty".to_string(), "fifty".to_string()],
+	]);
+	assert_eq!(splits(&MatcherConfig { max_split_legs: 2, ..MatcherConfig::default() }, &candidates, &target), vec![
+		vec!["hundred".to_string(), "two-hundred".to_string()],
+	]);
+}
+
+#[test]
+fn settles_several_top_ups_with_one_incoming_transaction() {
+	let target = normal("settled", "card", 10, 90);
+	let candidates = [normal("top-up-1", "checking", 8, -40), normal("top-up-2", "checking", 9, -50)];
+
+	assert_eq!(splits(&MatcherConfig::default(), &candidates, &target), vec![
+		vec!["top-up-1".to_string(), "top-up-2".to_string()],
+	]);
+}
@@ -0,0 +1,35 @@
+use njord::interactions::side_by_side;
+use njord::matcher::{NormalTransaction, Transaction};
+use njord::nordigen::transaction::RawTransaction;
+use rust_decimal::Decimal;
+
+mod common;
+use common::may;
+
+fn normal(id: &str, account_id: &str, amount: i64, remittance_information: &str) -> NormalTransaction {
+	let (transaction, account) = common::raw(id, account_id, may(10), Decimal::new(amount, 0));
+	let raw_transaction = (RawTransaction { remittance_information: Some(remittance_information.into()), ..transaction }, account);
+	let Transaction::Normal(transaction) = Transaction::from(&raw_transaction) else { unreachable!() };
+	transaction
+}
+
+#[test]
+fn wraps_long_remittance_information_instead_of_cutting_it_off() {
+	let target = normal("out", "checking", -100, "Invoice 2023-0417 for garden maintenance in April and May, reference RF18539007547034ABCDEFGHIJKLMNOPQRS");
+	let candidate = normal("in", "savings", 100, "Short");
+
+	let table = side_by_side(&[("This transaction".to_string(), &target, None), ("Candidate".to_string(), &candidate, None)]);
+	let remittance = table.lines()
+		.skip_while(|line| !line.starts_with("Remittance"))
+		.take_while(|line| line.starts_with("Remittance") || line.starts_with(' '))
+		.collect::<Vec<_>>();
+
+	assert!(!table.contains('…'), "{table}");
+	assert_eq!(remittance.iter().map(|line| line.trim_end()).collect::<Vec<_>>(), vec![
+		"Remittance    Invoice 2023-0417 for garden       Short",
+		"              maintenance in April and May,",
+		"              reference",
+		"              RF18539007547034ABCDEFGHIJKLMNOPQR",
+		"              S",
+	]);
+}
@@ -0,0 +1,69 @@
+use std::rc::Rc;
+use njord::matcher::config::MatcherConfig;
+use njord::matcher::decisions::Decisions;
+use njord::matcher::rules::TransferRule;
+use njord::matcher::{match_transactions, NormalTransaction, Transaction};
+use njord::nordigen::account::Account;
+use njord::nordigen::transaction::RawTransaction;
+use rust_decimal::Decimal;
+
+mod common;
+use common::{may, transfers};
+
+fn raw(id: &str, account_id: &str, day: u32, amount: i64, description: &str) -> (RawTransaction, Rc<Account>) {
+	let (transaction, account) = common::raw(id, account_id, may(day), Decimal::new(amount, 0));
+	(RawTransaction { additional_info: Some(description.into()), ..transaction }, account)
+}
+
+fn normal(raw_transaction: &(RawTransaction, Rc<Account>)) -> NormalTransaction {
+	let Transaction::Normal(transaction) = Transaction::from(raw_transaction) else { unreachable!() };
+	transaction
+}
+
+#[test]
+fn suggests_a_rule_after_repeated_decisions_between_the_same_accounts() {
+	let config = MatcherConfig::default();
+	let mut decisions = Decisions::default();
+
+	for (day, month) in [(1, "February"), (2, "March"), (3, "April")] {
+		let out = normal(&raw(&format!("out-{day}"), "checking", day, -200, &format!("Savings {month}")));
+		let incoming = normal(&raw(&format!("in-{day}"), "savings", day, 200, &format!("From checking {month}")));
+		assert!(decisions.suggest_rule(&config, &out, &incoming).is_none());
+		decisions.record(&out, &[&incoming], Some(&incoming));
+	}
+
+	let out = normal(&raw("out-4", "checking", 4, -200, "Savings May"));
+	let incoming = normal(&raw("in-4", "savings", 4, 200, "From checking May"));
+	let rule = decisions.suggest_rule(&config, &out, &incoming).unwrap();
+	assert_eq!((rule.from_account.as_str(), rule.to_account.as_str()), ("checking", "savings"));
+	assert_eq!(rule.description_contains.as_deref(), Some("savings"));
+}
+
+#[test]
+fn pairs_with_the_account_a_rule_names_without_asking() {
+	let raw_transactions = [
+		raw("out", "checking", 10, -200, "Savings June"),
+		raw("to-savings", "savings", 10, 200, "From checking"),
+		raw("to-broker", "broker", 10, 200, "From checking"),
+	];
+	let matched = |decisions: &mut Decisions| transfers(&match_transactions(&raw_transactions, vec![], &MatcherConfig::default(), decisions, false).unwrap());
+
+	let mut decisions = Decisions::default();
+	assert_eq!(matched(&mut decisions), vec![]);
+
+	decisions.rules.push(TransferRule {
+		from_account: "checking".into(),
+		to_account: "savings".into(),
+		description_contains: Some("savings".into()),
+		label: "checking -> savings".into(),
+		enabled: true,
+	});
+	assert_eq!(matched(&mut decisions), vec![("out".to_string(), "to-savings".to_string())]);
+
+	decisions.rules[0].description_contains = Some("rent".into());
+	assert_eq!(matched(&mut decisions), vec![]);
+
+	decisions.rules[0].description_contains = None;
+	decisions.rules[0].enabled = false;
+	assert_eq!(matched(&mut decisions), vec![]);
+}
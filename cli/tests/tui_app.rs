@@ -0,0 +1,77 @@
+#![cfg(feature = "tui")]
+
+use njord::matcher::decisions::{Decisions, Verdict};
+use njord::matcher::{NormalTransaction, Transaction};
+use njord::tui::app::{App, Mode};
+use rust_decimal::Decimal;
+
+mod common;
+use common::may;
+
+fn transactions() -> Vec<Transaction> {
+	[
+		common::raw("a-out", "a", may(1), Decimal::new(-100, 0)),
+		common::raw("b-in", "b", may(20), Decimal::new(100, 0)),
+		common::raw("a-in", "a", may(3), Decimal::new(100, 0)),
+	].iter().map(Transaction::from).collect()
+}
+
+fn normal<'a>(transactions: &'a [Transaction], id: &str) -> &'a NormalTransaction {
+	transactions.iter()
+		.find_map(|transaction| match transaction {
+			Transaction::Normal(transaction) if transaction.id == id => Some(transaction),
+			_ => None,
+		})
+		.unwrap()
+}
+
+#[test]
+fn pairs_marked_transactions_and_unpairs_them_again() {
+	let mut decisions = Decisions::default();
+	let mut app = App::new(transactions(), &mut decisions);
+
+	app.toggle_mark();
+	assert_eq!(app.marked, Some(0));
+	app.move_by(2);
+	app.pair_marked();
+	assert_eq!(app.status, "Both transactions are in the same account");
+	assert_eq!(app.transactions.len(), 3);
+
+	app.move_by(-1);
+	app.pair_marked();
+	assert_eq!(app.status, "Paired");
+	assert_eq!(app.marked, None);
+	assert_eq!(common::transfers(&app.transactions), vec![("a-out".to_string(), "b-in".to_string())]);
+
+	app.move_by(-1);
+	app.unpair();
+	assert_eq!(app.status, "Unpaired");
+	assert_eq!(common::transfers(&app.transactions), vec![]);
+	let transactions = app.transactions;
+
+	// Undoing the pairing is remembered as a rejection, so the matcher doesn't pair them again next time.
+	let verdict = decisions.verdict(normal(&transactions, "a-out"), normal(&transactions, "b-in"));
+	assert!(matches!(verdict, Some(Verdict::Rejected)));
+}
+
+#[test]
+fn filters_rows_and_edits_the_selected_transaction() {
+	let mut decisions = Decisions::default();
+	let mut app = App::new(transactions(), &mut decisions);
+
+	app.set_filter("account:b");
+	assert_eq!(app.visible, vec![1]);
+	assert_eq!(app.status, "1 of 3 rows shown");
+
+	app.set_filter("min:lots");
+	assert_eq!(app.status, "lots is not a valid amount");
+	assert_eq!(app.visible, vec![1]);
+
+	app.start_edit(true);
+	let Mode::EditCategory(input) = &mut app.mode else { panic!("not editing the category") };
+	input.push_str(" Savings ");
+	app.finish_edit();
+
+	assert!(matches!(app.mode, Mode::Browse));
+	assert_eq!(normal(&app.transactions, "b-in").category.as_deref(), Some("Savings"));
+}
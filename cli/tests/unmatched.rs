@@ -0,0 +1,50 @@
+use std::rc::Rc;
+use chrono::{Duration, Local, NaiveDate};
+use njord::matcher::config::MatcherConfig;
+use njord::matcher::decisions::Decisions;
+use njord::matcher::unmatched::UnmatchedStore;
+use njord::matcher::{match_transactions, Transaction};
+use njord::nordigen::account::Account;
+use njord::nordigen::transaction::RawTransaction;
+use rust_decimal::Decimal;
+
+mod common;
+
+fn raw(id: &str, account_id: &str, date: NaiveDate, amount: i64) -> (RawTransaction, Rc<Account>) {
+	common::raw(id, account_id, date, Decimal::new(amount, 0))
+}
+
+fn matched(raw_transactions: &[(RawTransaction, Rc<Account>)], previously_exported: UnmatchedStore) -> Vec<Transaction> {
+	let previously_exported = previously_exported.into_transactions(raw_transactions);
+	match_transactions(raw_transactions, previously_exported, &MatcherConfig::default(), &mut Decisions::default(), false).unwrap()
+}
+
+#[test]
+fn pairs_with_a_transaction_exported_unmatched_in_an_earlier_run() {
+	let today = Local::now().date_naive();
+
+	let first_run = matched(&[raw("out", "checking", today - Duration::days(1), -100)], UnmatchedStore::default());
+	let store = UnmatchedStore::from_transactions(&first_run, 30);
+
+	// Booked the same day, but only listed by the bank after the first run.
+	let second_run = matched(&[raw("in", "savings", today - Duration::days(1), 100)], store);
+	let [Transaction::Correction(correction), Transaction::Transfer(transfer)] = second_run.as_slice() else { panic!("{second_run:?}") };
+	assert_eq!(correction.id, "out");
+	assert_eq!(common::transfers(&second_run), vec![("out".to_string(), "in".to_string())]);
+	assert!(transfer.sources[0].previously_exported);
+}
+
+#[test]
+fn forgets_transactions_past_the_retention_or_fetched_again() {
+	let today = Local::now().date_naive();
+	let first_run = matched(&[
+		raw("old", "checking", today - Duration::days(40), -100),
+		raw("recent", "checking", today - Duration::days(2), -50),
+	], UnmatchedStore::default());
+
+	let store = UnmatchedStore::from_transactions(&first_run, 30);
+	assert_eq!(store.transactions.iter().map(|stored| stored.id.as_str()).collect::<Vec<_>>(), vec!["recent"]);
+
+	let fetched_again = [raw("recent", "checking", today - Duration::days(2), -50)];
+	assert_eq!(store.into_transactions(&fetched_again).len(), 0);
+}